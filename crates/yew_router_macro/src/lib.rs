@@ -4,7 +4,7 @@ use proc_macro::TokenStream;
 mod switch;
 
 /// Implements `Switch` trait based on attributes present on the struct or enum variants.
-#[proc_macro_derive(Switch, attributes(to, lit, cap, rest, query, frag))]
+#[proc_macro_derive(Switch, attributes(to, lit, cap, rest, query, frag, redirect, not_found, fallback))]
 pub fn switch(tokens: TokenStream) -> TokenStream {
     crate::switch::switch_impl(tokens)
 }
@@ -38,3 +38,18 @@ pub fn query(_: TokenStream, _: TokenStream) -> TokenStream {
 pub fn frag(_: TokenStream, _: TokenStream) -> TokenStream {
     TokenStream::new()
 }
+
+#[proc_macro_attribute]
+pub fn redirect(_: TokenStream, _: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
+
+#[proc_macro_attribute]
+pub fn not_found(_: TokenStream, _: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
+
+#[proc_macro_attribute]
+pub fn fallback(_: TokenStream, _: TokenStream) -> TokenStream {
+    TokenStream::new()
+}
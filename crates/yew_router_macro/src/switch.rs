@@ -24,6 +24,10 @@ pub struct SwitchItem {
     pub matcher: Vec<ShadowMatcherToken>,
     pub ident: Ident,
     pub fields: Fields,
+    /// The route provided by the `redirect` attribute.
+    pub redirect: Option<Vec<ShadowMatcherToken>>,
+    /// If the variant is matched when no other variant is.
+    pub fallback: bool,
 }
 
 pub fn switch_impl(input: TokenStream) -> TokenStream {
@@ -33,6 +37,7 @@ pub fn switch_impl(input: TokenStream) -> TokenStream {
 
     match input.data {
         Data::Struct(ds) => {
+            let redirect = AttrToken::redirect_from_attributes(&input.attrs);
             let mut encountered_query = false;
             let matcher = AttrToken::convert_attributes_to_tokens(input.attrs)
                 .into_iter()
@@ -44,6 +49,8 @@ pub fn switch_impl(input: TokenStream) -> TokenStream {
                 matcher,
                 ident,
                 fields: ds.fields,
+                redirect,
+                fallback: false,
             };
             generate_struct_impl(switch_item)
         }
@@ -52,28 +59,66 @@ pub fn switch_impl(input: TokenStream) -> TokenStream {
                 .variants
                 .into_iter()
                 .map(|variant: Variant| {
-                    let mut encountered_query = false;
-                    let matcher = AttrToken::convert_attributes_to_tokens(variant.attrs)
-                        .into_iter()
-                        .enumerate()
-                        .map(|(index, at)| {
-                            at.into_shadow_matcher_tokens(index, &mut encountered_query)
-                        })
-                        .flatten()
-                        .collect::<Vec<_>>();
+                    let redirect = AttrToken::redirect_from_attributes(&variant.attrs);
+                    let fallback = AttrToken::is_fallback(&variant.attrs);
+                    let matcher = if fallback {
+                        fallback_matcher(&variant.fields)
+                    } else {
+                        let mut encountered_query = false;
+                        AttrToken::convert_attributes_to_tokens(variant.attrs)
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, at)| {
+                                at.into_shadow_matcher_tokens(index, &mut encountered_query)
+                            })
+                            .flatten()
+                            .collect::<Vec<_>>()
+                    };
                     SwitchItem {
                         matcher,
                         ident: variant.ident,
                         fields: variant.fields,
+                        redirect,
+                        fallback,
                     }
                 })
                 .collect::<Vec<SwitchItem>>();
+            if switch_variants.iter().filter(|sv| sv.fallback).count() > 1 {
+                panic!("Only one variant can be marked with `not_found` or `fallback`.")
+            }
             generate_enum_impl(ident, switch_variants)
         }
         Data::Union(_du) => panic!("Deriving FromCaptures not supported for Unions."),
     }
 }
 
+/// The fallback variant isn't matched using its attributes, but the route is still serialized
+/// using a matcher that writes the field holding the unmatched route.
+fn fallback_matcher(fields: &Fields) -> Vec<ShadowMatcherToken> {
+    match fields {
+        Fields::Named(fields_named) if fields_named.named.len() == 1 => {
+            let name = fields_named.named[0]
+                .ident
+                .as_ref()
+                .expect("Named fields have an ident")
+                .to_string();
+            vec![ShadowMatcherToken::Capture(
+                ShadowCaptureVariant::ManyNamed(name),
+            )]
+        }
+        Fields::Unnamed(fields_unnamed) if fields_unnamed.unnamed.len() == 1 => {
+            vec![ShadowMatcherToken::Capture(
+                ShadowCaptureVariant::ManyNamed("0".to_string()),
+            )]
+        }
+        Fields::Unit => vec![],
+        _ => panic!(
+            "A `not_found` or `fallback` variant can have at most one field, which holds the \
+             unmatched route."
+        ),
+    }
+}
+
 trait Flatten<T> {
     /// Because flatten is a nightly feature. I'm making a new variant of the function here for
     /// stable use. The naming is changed to avoid this getting clobbered when object_flattening
@@ -161,6 +206,7 @@ pub fn build_serializer_for_enum(
             matcher,
             ident,
             fields,
+            ..
         } = switch_item;
         match fields {
             Fields::Named(fields_named) => {
@@ -227,6 +273,7 @@ pub fn build_serializer_for_struct(switch_item: &SwitchItem, item: &Ident) -> To
         matcher,
         ident,
         fields,
+        ..
     } = switch_item;
     let destructor_and_writers = match fields {
         Fields::Named(fields_named) => {
@@ -281,6 +328,140 @@ pub fn build_serializer_for_struct(switch_item: &SwitchItem, item: &Ident) -> To
     }
 }
 
+/// Builds the `redirect` method for the variants that have a `redirect` attribute.
+///
+/// Returns `None` if no variant redirects, in which case the default implementation is used.
+pub fn build_redirect_for_enum(
+    switch_items: &[SwitchItem],
+    enum_ident: &Ident,
+) -> Option<TokenStream2> {
+    let arms = switch_items
+        .iter()
+        .filter_map(|switch_item: &SwitchItem| {
+            let redirect = switch_item.redirect.as_ref()?;
+            let ident = &switch_item.ident;
+            let pattern = destructure_for_redirect(
+                quote! {#enum_ident::#ident},
+                &switch_item.fields,
+                redirect,
+            );
+            let writers = write_redirect(redirect, &switch_item.fields);
+            Some(quote! {
+                #pattern => {
+                    #writers
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    if arms.is_empty() {
+        return None;
+    }
+    Some(quote! {
+        fn redirect(&self) -> Option<String> {
+            #[allow(unreachable_patterns)]
+            match self {
+                #(#arms)*
+                _ => None,
+            }
+        }
+    })
+}
+
+/// Builds the `redirect` method for a struct, if it has a `redirect` attribute.
+pub fn build_redirect_for_struct(switch_item: &SwitchItem) -> Option<TokenStream2> {
+    let redirect = switch_item.redirect.as_ref()?;
+    let ident = &switch_item.ident;
+    let pattern = destructure_for_redirect(quote! {#ident}, &switch_item.fields, redirect);
+    let writers = write_redirect(redirect, &switch_item.fields);
+    Some(quote! {
+        fn redirect(&self) -> Option<String> {
+            let #pattern = self;
+            #writers
+        }
+    })
+}
+
+/// Destructures the fields that are needed to write the redirect route.
+///
+/// Named fields are only bound if they are captured by the redirect route.
+fn destructure_for_redirect(
+    path: TokenStream2,
+    fields: &Fields,
+    redirect: &[ShadowMatcherToken],
+) -> TokenStream2 {
+    match fields {
+        Fields::Named(_) => {
+            let mut field_names: Vec<Ident> = vec![];
+            for token in redirect {
+                if let ShadowMatcherToken::Capture(capture) = token {
+                    let name = Ident::new(capture_name(capture), Span::call_site());
+                    if !field_names.contains(&name) {
+                        field_names.push(name);
+                    }
+                }
+            }
+            quote! {
+                #path{#(#field_names,)* ..}
+            }
+        }
+        Fields::Unnamed(fields_unnamed) => {
+            let field_names = fields_unnamed
+                .unnamed
+                .iter()
+                .enumerate()
+                .map(|(index, _)| unnamed_field_index_item(index));
+            quote! {
+                #path(#(#field_names),*)
+            }
+        }
+        Fields::Unit => path,
+    }
+}
+
+/// Writes the redirect route using the destructured fields.
+///
+/// Captures refer to named fields by name, and to unnamed fields in the order they appear.
+fn write_redirect(redirect: &[ShadowMatcherToken], fields: &Fields) -> TokenStream2 {
+    let mut item_count = 0;
+    let writers = redirect.iter().map(|token| match token {
+        ShadowMatcherToken::Exact(lit) => quote! {
+            buf.push_str(#lit);
+        },
+        ShadowMatcherToken::Capture(capture) => {
+            let name = match fields {
+                Fields::Unnamed(_) => {
+                    let name = unnamed_field_index_item(item_count);
+                    item_count += 1;
+                    name
+                }
+                Fields::Named(_) | Fields::Unit => {
+                    Ident::new(capture_name(capture), Span::call_site())
+                }
+            };
+            quote! {
+                ::yew_router::Switch::build_route_section::<()>(
+                    ::std::clone::Clone::clone(#name),
+                    &mut buf
+                );
+            }
+        }
+        ShadowMatcherToken::End => quote! {},
+    });
+    quote! {
+        let mut buf = String::new();
+        #(#writers)*
+        Some(buf)
+    }
+}
+
+fn capture_name(capture: &ShadowCaptureVariant) -> &str {
+    match capture {
+        ShadowCaptureVariant::Named(name)
+        | ShadowCaptureVariant::ManyNamed(name)
+        | ShadowCaptureVariant::NumberedNamed { name, .. } => name,
+    }
+}
+
 /// Creates an ident used for destructuring unnamed fields.
 ///
 /// There needs to be a unified way to "mangle" the unnamed fields so they can be destructured,
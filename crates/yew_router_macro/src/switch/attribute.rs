@@ -74,6 +74,39 @@ impl AttrToken {
             .collect()
     }
 
+    /// Finds the route provided by a `#[redirect = "/route"]` attribute, if one is present.
+    pub fn redirect_from_attributes(attributes: &[Attribute]) -> Option<Vec<ShadowMatcherToken>> {
+        attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .filter_map(|meta: Meta| match meta {
+                Meta::NameValue(mnv) if mnv.path.is_ident("redirect") => match &mnv.lit {
+                    Lit::Str(s) => Some(s.value()),
+                    _ => panic!("Value provided after `redirect` must be a String"),
+                },
+                _ => None,
+            })
+            .map(|redirect: String| {
+                yew_router_route_parser::parse_str_and_optimize_tokens(&redirect)
+                    .expect("Invalid redirect")
+                    .into_iter()
+                    .map(ShadowMatcherToken::from)
+                    .collect()
+            })
+            .next()
+    }
+
+    /// Checks if the variant is marked with `#[not_found]` or `#[fallback]`.
+    pub fn is_fallback(attributes: &[Attribute]) -> bool {
+        attributes
+            .iter()
+            .filter_map(|attr: &Attribute| attr.parse_meta().ok())
+            .any(|meta: Meta| match meta {
+                Meta::Path(path) => path.is_ident("not_found") || path.is_ident("fallback"),
+                _ => false,
+            })
+    }
+
     /// The id is an unique identifier that allows otherwise unnamed captures to still be captured
     /// with unique names.
     pub fn into_shadow_matcher_tokens(
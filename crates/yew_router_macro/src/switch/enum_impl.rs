@@ -1,15 +1,16 @@
-use crate::switch::{build_serializer_for_enum, SwitchItem};
+use crate::switch::{build_redirect_for_enum, build_serializer_for_enum, SwitchItem};
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
 use syn::{export::TokenStream2, Field, Fields, Ident, Type};
 
 pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -> TokenStream {
-    let variant_matchers = switch_variants.iter().map(|sv| {
+    let variant_matchers = switch_variants.iter().filter(|sv| !sv.fallback).map(|sv| {
         let SwitchItem {
             matcher,
             ident,
             fields,
+            ..
         } = sv;
         let build_from_captures = build_variant_from_captures(&enum_ident, ident, fields);
         let matcher = super::build_matcher_from_tokens(&matcher);
@@ -23,6 +24,8 @@ pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -
 
     let match_item = Ident::new("self", Span::call_site());
     let serializer = build_serializer_for_enum(&switch_variants, &enum_ident, &match_item);
+    let redirect = build_redirect_for_enum(&switch_variants, &enum_ident);
+    let fallback = build_fallback(&enum_ident, switch_variants.iter().find(|sv| sv.fallback));
 
     let token_stream = quote! {
         impl ::yew_router::Switch for #enum_ident {
@@ -31,7 +34,7 @@ pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -
                 let route_string = route.route;
                 #(#variant_matchers)*
 
-                return (None, state)
+                #fallback
             }
 
             fn build_route_section<T>(self, mut buf: &mut String) -> Option<T> {
@@ -41,11 +44,57 @@ pub fn generate_enum_impl(enum_ident: Ident, switch_variants: Vec<SwitchItem>) -
                 // Return None for now, because marking routes isn't supported yet.
                 #serializer
             }
+
+            #redirect
         }
     };
     TokenStream::from(token_stream)
 }
 
+/// Builds the end of `from_route_part`, which is reached when no other variant has matched.
+///
+/// The fallback variant is given the whole route.
+fn build_fallback(enum_ident: &Ident, fallback: Option<&SwitchItem>) -> TokenStream2 {
+    let SwitchItem { ident, fields, .. } = match fallback {
+        Some(fallback) => fallback,
+        None => {
+            return quote! {
+                return (None, state)
+            }
+        }
+    };
+    let field = match fields {
+        Fields::Named(fields_named) => fields_named.named.first(),
+        Fields::Unnamed(fields_unnamed) => fields_unnamed.unnamed.first(),
+        Fields::Unit => None,
+    };
+    let field_ty = match field {
+        Some(field) => &field.ty,
+        None => {
+            return quote! {
+                return (Some(#enum_ident::#ident), state)
+            }
+        }
+    };
+    let build_variant = match field.and_then(|field| field.ident.as_ref()) {
+        Some(field_name) => quote! {
+            |#field_name| #enum_ident::#ident { #field_name }
+        },
+        None => quote! {
+            #enum_ident::#ident
+        },
+    };
+    quote! {
+        let (value, state) = <#field_ty as ::yew_router::Switch>::from_route_part(
+            ::yew_router::route::Route {
+                route: route_string,
+                state,
+            }
+        );
+        return (value.map(#build_variant), state)
+    }
+}
+
 /// Once the 'captures' exists, attempt to populate the fields from the list of captures.
 fn build_variant_from_captures(
     enum_ident: &Ident,
@@ -11,12 +11,14 @@ pub fn generate_struct_impl(item: SwitchItem) -> TokenStream {
         matcher,
         ident,
         fields,
+        ..
     } = &item;
     let build_from_captures = build_struct_from_captures(&ident, &fields);
     let matcher = super::build_matcher_from_tokens(&matcher);
 
     let match_item = Ident::new("self", Span::call_site());
     let serializer = super::build_serializer_for_struct(&item, &match_item);
+    let redirect = super::build_redirect_for_struct(&item);
 
     let token_stream = quote! {
         impl ::yew_router::Switch for #ident {
@@ -34,6 +36,8 @@ pub fn generate_struct_impl(item: SwitchItem) -> TokenStream {
             fn build_route_section<T>(self, mut buf: &mut String) -> Option<T> {
                 #serializer
             }
+
+            #redirect
         }
     };
     TokenStream::from(token_stream)
@@ -3,10 +3,14 @@ use crate::{
     page::{Page, PageProps},
 };
 use yew::{html::ChildrenWithProps, prelude::*, Properties};
-use yew_router::{agent::RouteRequest::GetCurrentRoute, matcher::RouteMatcher, prelude::*};
+use yew_router::{
+    agent::RouteRequest::{GetCurrentRoute, InterceptAnchorClicks},
+    matcher::RouteMatcher,
+    prelude::*,
+};
 
 pub struct Guide {
-    router_agent: Box<dyn Bridge<RouteAgent>>,
+    router_agent: RouteAgentBridge,
     route: Option<Route>,
     props: GuideProps,
 }
@@ -26,7 +30,7 @@ impl Component for Guide {
 
     fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let callback = link.send_back(Msg::UpdateRoute);
-        let router_agent = RouteAgent::bridge(callback);
+        let router_agent = RouteAgentBridge::new(callback);
         Guide {
             router_agent,
             route: None,
@@ -36,6 +40,8 @@ impl Component for Guide {
 
     fn mounted(&mut self) -> ShouldRender {
         self.router_agent.send(GetCurrentRoute);
+        // Links in the rendered markdown are plain anchors.
+        self.router_agent.send(InterceptAnchorClicks(true));
         false
     }
 
@@ -29,12 +29,11 @@ impl Component for Model {
 
     fn create(_: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let mut route_service: RouteService<()> = RouteService::new();
-        let route = route_service.get_route();
-        let route = Route::from(route);
+        let route = Route::current_route(&route_service);
         let callback = link.send_back(|(route, state)| -> Msg {
             Msg::RouteChanged(Route {
                 route,
-                state: Some(state),
+                state: state.ok(),
             })
         });
         route_service.register_callback(callback);
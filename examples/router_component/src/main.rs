@@ -38,7 +38,7 @@ impl Component for Model {
     }
 }
 
-#[derive(Debug, Switch)]
+#[derive(Debug, Switch, PartialEq, Clone)]
 pub enum AppRoute {
     #[to = "/a{*:inner}"]
     A(ARoute),
@@ -48,6 +48,9 @@ pub enum AppRoute {
     C,
     #[to = "/e/{string}"]
     E(String),
+    #[to = "/hello/{string}"]
+    #[redirect = "/e/{string}"]
+    Hello(String),
 }
 
 #[derive(Debug, Switch, PartialEq, Clone)]
@@ -70,10 +73,11 @@ impl Renderable<Model> for Model {
                 <nav class="menu",>
                     <RouterButton: text=String::from("Go to A"), link="/a", />
                     <RouterLink: text=String::from("Go to B"), link="/b/#", />
-                    <RouterButton: text=String::from("Go to C"), link="/c", />
+                    <RouterButton<AppRoute> text=String::from("Go to C") link=AppRoute::C />
                     <RouterButton: text=String::from("Go to A/C"), link="/a/c", />
                     <RouterButton: text=String::from("Go to E (hello there)"), link="/e/there", />
                     <RouterButton: text=String::from("Go to E (hello world)"), link="/e/world", />
+                    <RouterButton: text=String::from("Go to hello (redirects to E)"), link="/hello/redirect", />
                     <RouterButton: text=String::from("Go to bad path"), link="/a_bad_path", />
                 </nav>
                 <div>
@@ -87,6 +91,8 @@ impl Renderable<Model> for Model {
                                 },
                                 Some(AppRoute::C) => html!{<CModel />},
                                 Some(AppRoute::E(string)) => html!{format!("hello {}", string)},
+                                // The router replaces the route before this can be rendered.
+                                Some(AppRoute::Hello(_)) => html!{},
                                 None => html!{"404"}
                             }
                         })
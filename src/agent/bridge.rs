@@ -1,7 +1,8 @@
 //! Bridge to RouteAgent.
 use crate::{
-    agent::{AgentState, RouteAgent},
-    route::Route,
+    agent::{AgentState, RouteAgent, RouteChange, RouteInterest, RouteRequest, RouteResponse},
+    route::{Route, RouteScope},
+    switch::{build_route_from_switch, Switch},
 };
 use std::{
     fmt::{Debug, Error as FmtError, Formatter},
@@ -13,6 +14,8 @@ use yew::{
 };
 
 /// A simplified interface to the router agent.
+///
+/// The bridge stops receiving route changes from the agent as soon as it is dropped.
 pub struct RouteAgentBridge<T>(Box<dyn Bridge<RouteAgent<T>>>)
 where
     for<'de> T: AgentState<'de>;
@@ -22,7 +25,67 @@ where
     for<'de> T: AgentState<'de>,
 {
     /// Creates a new bridge.
+    ///
+    /// The callback is only called when the route changes.
     pub fn new(callback: Callback<Route<T>>) -> Self {
+        Self::with_changes(callback.reform(|change: RouteChange<T>| change.route))
+    }
+
+    /// Creates a new bridge, whose callback is told how the route was reached when it changes.
+    pub fn with_changes(callback: Callback<RouteChange<T>>) -> Self {
+        Self::with_responses(route_changes(callback))
+    }
+
+    /// Creates a new bridge, whose callback is only called when the route changes to one that the
+    /// switch matches.
+    ///
+    /// # Example
+    /// ```
+    /// # use yew::Callback;
+    /// # use yew_router::{agent::RouteAgentBridge, Switch};
+    /// #[derive(Switch)]
+    /// enum Settings {
+    ///     #[to = "/settings/{section}"]
+    ///     Section(String),
+    /// }
+    /// # fn dont_execute() {
+    /// let callback = Callback::from(|settings: Settings| {
+    ///     // Only called for routes starting with `/settings/`.
+    /// });
+    /// let bridge = RouteAgentBridge::<()>::with_switch(callback);
+    /// # }
+    /// ```
+    pub fn with_switch<SW: Switch + 'static>(callback: Callback<SW>) -> Self {
+        Self::with_changes(Callback::from(move |change: RouteChange<T>| {
+            if let Some(switch) = SW::switch(change.route) {
+                callback.emit(switch)
+            }
+        }))
+    }
+
+    /// Creates a new bridge, whose callback is only called when the route changes to one that
+    /// starts with the prefix.
+    ///
+    /// The prefix has to end at the boundary of a path section, so routes starting with
+    /// `/settingsfoo` aren't considered to start with `/settings`.
+    ///
+    /// The bridge registers its interest with the agent, so the agent doesn't send it changes
+    /// between routes outside of the prefix.
+    pub fn with_prefix(prefix: &str, callback: Callback<Route<T>>) -> Self {
+        let scope = RouteScope::new(prefix);
+        let mut bridge = Self::with_changes(Callback::from(move |change: RouteChange<T>| {
+            if scope.contains(&change.route.route) {
+                callback.emit(change.route)
+            }
+        }));
+        bridge.set_interest(RouteInterest::prefix(prefix));
+        bridge
+    }
+
+    /// Creates a new bridge that receives every response sent by the agent.
+    ///
+    /// This is needed in order to act as a deferred navigation guard.
+    pub fn with_responses(callback: Callback<RouteResponse<T>>) -> Self {
         let router_agent = RouteAgent::bridge(callback);
         RouteAgentBridge(router_agent)
     }
@@ -32,9 +95,38 @@ where
     /// Directly spawn a new Router
     pub fn spawn(callback: Callback<Route<T>>) -> Self {
         use yew::agent::Discoverer;
-        let router_agent = Context::spawn_or_join(Some(callback));
+        let callback = callback.reform(|change: RouteChange<T>| change.route);
+        let router_agent = Context::spawn_or_join(Some(route_changes(callback)));
         RouteAgentBridge(router_agent)
     }
+
+    /// Limits which route changes the agent sends to this bridge.
+    pub fn set_interest(&mut self, interest: RouteInterest) {
+        self.0.send(RouteRequest::SetInterest(interest));
+    }
+
+    /// Changes the route to the one built from the switch and alerts connected components to the
+    /// route change.
+    pub fn change_route_to<SW: Switch>(&mut self, switch: SW) {
+        let route = build_route_from_switch(switch);
+        self.0.send(RouteRequest::ChangeRoute(route));
+    }
+
+    /// Replaces the most recent route with the one built from the switch and alerts connected
+    /// components to the route change.
+    pub fn replace_route_to<SW: Switch>(&mut self, switch: SW) {
+        let route = build_route_from_switch(switch);
+        self.0.send(RouteRequest::ReplaceRoute(route));
+    }
+}
+
+/// Adapts a callback so it only receives route changes.
+fn route_changes<T: 'static>(callback: Callback<RouteChange<T>>) -> Callback<RouteResponse<T>> {
+    Callback::from(move |response| {
+        if let RouteResponse::RouteChanged(change) = response {
+            callback.emit(change)
+        }
+    })
 }
 
 /// A wrapper around the bridge
@@ -47,6 +139,12 @@ impl<T: for<'de> AgentState<'de>> Debug for RouteAgentBridge<T> {
     }
 }
 
+impl<T: for<'de> AgentState<'de>> Drop for RouteAgentBridge<T> {
+    fn drop(&mut self) {
+        self.0.send(RouteRequest::Disconnect);
+    }
+}
+
 impl<T: for<'de> AgentState<'de>> Deref for RouteAgentBridge<T> {
     type Target = Box<dyn Bridge<RouteAgent<T>>>;
 
@@ -1,5 +1,8 @@
 //! Bridge to RouteAgent.
-use crate::agent::{AgentState, RouteAgent};
+use crate::{
+    agent::{AgentState, RouteAgent, RouteRequest},
+    switch::{build_route_from_switch, Switch},
+};
 use std::{
     fmt::{Debug, Error as FmtError, Formatter},
     ops::{Deref, DerefMut},
@@ -20,6 +23,20 @@ where
         let dispatcher = RouteAgent::dispatcher();
         RouteAgentDispatcher(dispatcher)
     }
+
+    /// Changes the route to the one built from the switch and alerts connected components to the
+    /// route change.
+    pub fn change_route_to<SW: Switch>(&mut self, switch: SW) {
+        let route = build_route_from_switch(switch);
+        self.0.send(RouteRequest::ChangeRoute(route));
+    }
+
+    /// Replaces the most recent route with the one built from the switch and alerts connected
+    /// components to the route change.
+    pub fn replace_route_to<SW: Switch>(&mut self, switch: SW) {
+        let route = build_route_from_switch(switch);
+        self.0.send(RouteRequest::ReplaceRoute(route));
+    }
 }
 
 /// A wrapper around the bridge
@@ -0,0 +1,129 @@
+//! Guards that can veto or redirect navigation.
+use crate::route::{has_prefix, Route};
+use serde::{Deserialize, Serialize};
+use stdweb::{js, Value};
+
+/// Determines at which point of a navigation a guard is consulted.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum GuardHook {
+    /// Consulted when navigating away from a route within the guard's prefix.
+    BeforeLeave,
+    /// Consulted when navigating to a route within the guard's prefix.
+    BeforeEnter,
+}
+
+/// Determines how a guard arrives at its decision.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GuardCheck {
+    /// The agent asks the user synchronously using `window.confirm` with the provided message.
+    /// Navigation is vetoed if the user declines.
+    Confirm(String),
+    /// The agent sends the guard a
+    /// [ConfirmNavigation](enum.RouteResponse.html#variant.ConfirmNavigation) response and waits
+    /// for it to answer with a
+    /// [ResolveNavigation](enum.RouteRequest.html#variant.ResolveNavigation) request before
+    /// continuing.
+    Deferred,
+}
+
+/// A guard that can be registered with the `RouteAgent` using
+/// [RegisterGuard](enum.RouteRequest.html#variant.RegisterGuard).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NavigationGuard {
+    /// When the guard is consulted.
+    pub hook: GuardHook,
+    /// Only routes within this prefix are guarded.
+    /// The prefix has to end at the boundary of a path section, so `/admin` doesn't guard
+    /// `/administrator`.
+    /// An empty prefix guards every route.
+    pub prefix: String,
+    /// How the guard decides.
+    pub check: GuardCheck,
+}
+
+impl NavigationGuard {
+    /// Creates a guard that is consulted before leaving any route.
+    pub fn before_leave(check: GuardCheck) -> Self {
+        NavigationGuard {
+            hook: GuardHook::BeforeLeave,
+            prefix: String::new(),
+            check,
+        }
+    }
+
+    /// Creates a guard that is consulted before entering any route within `prefix`.
+    pub fn before_enter(prefix: &str, check: GuardCheck) -> Self {
+        NavigationGuard {
+            hook: GuardHook::BeforeEnter,
+            prefix: prefix.to_string(),
+            check,
+        }
+    }
+
+    /// Determines if this guard should be consulted when navigating between the two routes.
+    pub fn applies_to<T>(&self, from: &Route<T>, to: &Route<T>) -> bool {
+        match self.hook {
+            GuardHook::BeforeLeave => has_prefix(from, &self.prefix) && from.route != to.route,
+            GuardHook::BeforeEnter => has_prefix(to, &self.prefix),
+        }
+    }
+}
+
+/// A navigation that is waiting for a guard's decision.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PendingNavigation<T> {
+    /// Identifies the navigation when answering with
+    /// [ResolveNavigation](enum.RouteRequest.html#variant.ResolveNavigation).
+    pub id: u32,
+    /// The route that is being left.
+    pub from: Route<T>,
+    /// The route that is being navigated to.
+    pub to: Route<T>,
+    /// The hook the guard was registered with.
+    pub hook: GuardHook,
+}
+
+/// A guard's answer to a pending navigation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum GuardDecision<T> {
+    /// Let the navigation proceed.
+    Allow,
+    /// Cancel the navigation.
+    /// If the navigation was caused by the browser's back or forward buttons, the previous route
+    /// is restored.
+    Deny,
+    /// Cancel the navigation and navigate to the provided route instead.
+    Redirect(Route<T>),
+}
+
+/// Synchronously asks the user to confirm the navigation.
+pub(crate) fn confirm(message: &str) -> bool {
+    let confirmed: Value = js! { return window.confirm(@{message}); };
+    match confirmed {
+        Value::Bool(confirmed) => confirmed,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn before_enter_applies_within_prefix() {
+        let guard = NavigationGuard::before_enter("/admin", GuardCheck::Deferred);
+        let home = Route::<()>::from("/");
+        assert!(guard.applies_to(&home, &Route::from("/admin")));
+        assert!(guard.applies_to(&home, &Route::from("/admin/users?page=2")));
+        assert!(!guard.applies_to(&home, &Route::from("/administrator")));
+        assert!(!guard.applies_to(&Route::from("/admin"), &home));
+    }
+
+    #[test]
+    fn before_leave_applies_when_route_changes() {
+        let guard = NavigationGuard::before_leave(GuardCheck::Confirm("Leave?".to_string()));
+        let form = Route::<()>::from("/form");
+        assert!(guard.applies_to(&form, &Route::from("/")));
+        assert!(!guard.applies_to(&form, &form));
+    }
+}
@@ -0,0 +1,74 @@
+//! Limits which route changes subscribers of the agent are told about.
+use crate::route::{Route, RouteScope};
+use serde::{Deserialize, Serialize};
+
+/// Determines which route changes the `RouteAgent` sends to a subscriber.
+///
+/// Interest is registered using [SetInterest](enum.RouteRequest.html#variant.SetInterest).
+/// Responses to [GetCurrentRoute](enum.RouteRequest.html#variant.GetCurrentRoute) are always sent.
+///
+/// Filtering by anything else, like the `Switch` a route matches, has to happen in the
+/// subscriber, for example by using
+/// [RouteAgentBridge::with_switch](struct.RouteAgentBridge.html#method.with_switch).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RouteInterest {
+    /// Every route change is sent.
+    All,
+    /// Only changes to or from routes within any of the prefixes are sent, so subscribers also
+    /// learn when a route within a prefix is left.
+    ///
+    /// Prefixes have to end at the boundary of a path section, so `/settingsfoo` isn't within
+    /// `/settings`.
+    Prefixes(Vec<String>),
+    /// No route changes are sent.
+    None,
+}
+
+impl RouteInterest {
+    /// Creates an interest in the routes within the prefix.
+    pub fn prefix(prefix: &str) -> Self {
+        RouteInterest::Prefixes(vec![prefix.to_string()])
+    }
+
+    /// Determines if a subscriber with this interest should be told about the navigation between
+    /// the two routes.
+    pub fn applies_to<T>(&self, from: &Route<T>, to: &Route<T>) -> bool {
+        match self {
+            RouteInterest::All => true,
+            RouteInterest::Prefixes(prefixes) => prefixes.iter().any(|prefix| {
+                let scope = RouteScope::new(prefix);
+                scope.contains(&from.route) || scope.contains(&to.route)
+            }),
+            RouteInterest::None => false,
+        }
+    }
+}
+
+impl Default for RouteInterest {
+    fn default() -> Self {
+        RouteInterest::All
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_applies_when_entering_or_leaving() {
+        let interest = RouteInterest::prefix("/settings");
+        let settings = Route::<()>::from("/settings/profile");
+        let home = Route::<()>::from("/home");
+        assert!(interest.applies_to(&home, &settings));
+        assert!(interest.applies_to(&settings, &home));
+        assert!(!interest.applies_to(&home, &Route::from("/settingsfoo")));
+    }
+
+    #[test]
+    fn all_and_none() {
+        let from = Route::<()>::from("/a");
+        let to = Route::<()>::from("/b");
+        assert!(RouteInterest::All.applies_to(&from, &to));
+        assert!(!RouteInterest::None.applies_to(&from, &to));
+    }
+}
@@ -2,17 +2,19 @@
 //!
 //! It wraps a route service and allows calls to be sent to it to update every subscriber,
 //! or just the element that made the request.
-use crate::service::RouteService;
+use crate::service::{
+    HistoryEntry, RouteService, ScrollPosition, StateError, StatePersistence, TabMessage,
+};
 
 use yew::prelude::worker::*;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Error as FmtError, Formatter};
 
-use crate::route::{Route, RouteState};
-use log::trace;
+use crate::route::{QueryChange, Route, RouteState};
+use log::{trace, warn};
 
 mod bridge;
 pub use bridge::RouteAgentBridge;
@@ -20,6 +22,12 @@ pub use bridge::RouteAgentBridge;
 mod dispatcher;
 pub use dispatcher::RouteAgentDispatcher;
 
+mod guard;
+pub use guard::{GuardCheck, GuardDecision, GuardHook, NavigationGuard, PendingNavigation};
+
+mod interest;
+pub use interest::RouteInterest;
+
 /// Any state that can be used in the router agent must meet the criteria of this trait.
 pub trait AgentState<'de>: RouteState + Serialize + Deserialize<'de> + Debug {}
 impl<'de, T> AgentState<'de> for T where T: RouteState + Serialize + Deserialize<'de> + Debug {}
@@ -32,7 +40,11 @@ pub enum Void {}
 #[derive(Debug)]
 pub enum Msg<T> {
     /// Message for when the route is changed.
-    BrowserNavigationRouteChanged((String, T)),
+    BrowserNavigationRouteChanged((String, Result<T, StateError>)),
+    /// Message for when an anchor tag was clicked while clicks are being intercepted.
+    AnchorClicked(String),
+    /// Message for when another tab sent a message while tabs are synchronized.
+    TabMessageReceived(TabMessage<T>),
 }
 
 /// Input message type for interacting with the `RouteAgent'.
@@ -49,19 +61,217 @@ pub enum RouteRequest<T> {
     /// Changes the route using a Route struct, but does not alert connected components to the
     /// route change.
     ChangeRouteNoBroadcast(Route<T>),
+    /// Applies the changes to the query of the current route, leaving its path, fragment, state
+    /// and other query keys untouched, and alerts connected components to the route change.
+    UpdateQuery(Vec<QueryChange>),
+    /// Like `UpdateQuery`, but replaces the current route instead of creating a new history entry,
+    /// which is useful for changes that shouldn't be navigable using the back button.
+    ReplaceQuery(Vec<QueryChange>),
     /// Gets the current route.
     GetCurrentRoute,
-    /// Removes the entity from the Router Agent
-    // TODO this is a temporary message because yew currently doesn't call the destructor, so it
-    // must be manually engaged
+    /// Registers the entity as a guard that is consulted before navigation takes place.
+    ///
+    /// An entity can only register one guard; registering another replaces the previous one.
+    RegisterGuard(NavigationGuard),
+    /// Removes the entity's guard.
+    UnregisterGuard,
+    /// Answers a [ConfirmNavigation](enum.RouteResponse.html#variant.ConfirmNavigation) response
+    /// using the id of the pending navigation.
+    ResolveNavigation(u32, GuardDecision<T>),
+    /// Enables or disables scroll management by the agent, which is disabled by default.
+    ///
+    /// When enabled, the scroll position is kept up to date in the current history entry, and
+    /// restored when the browser navigates back to it.
+    /// New routes are scrolled to the top, unless they have a fragment, in which case the element
+    /// whose id matches the fragment is scrolled into view.
+    SetScrollRestoration(bool),
+    /// Enables or disables interception of clicks on plain `<a href>` tags anywhere in the
+    /// document, which is disabled by default.
+    ///
+    /// When enabled, clicking an anchor pointing to the same origin changes the route like
+    /// `ChangeRoute` would, instead of loading a new page.
+    /// Clicks using a modifier key or a button other than the primary one, and clicks on anchors
+    /// with a `target` other than `_self`, a `download` attribute, or a `data-router-ignore`
+    /// attribute on themselves or an ancestor are not intercepted.
+    /// Neither are clicks on anchors pointing to a fragment of the current page, which the browser
+    /// scrolls to, and clicks on anchors pointing to the current route are ignored.
+    InterceptAnchorClicks(bool),
+    /// Enables synchronization with the other tabs of the same origin whose agents use the channel
+    /// with the provided name, or disables it if `None` is provided.
+    ///
+    /// While enabled, every broadcast route change is announced to the other tabs, which send
+    /// their subscribers an
+    /// [OtherTabRouteChanged](enum.RouteResponse.html#variant.OtherTabRouteChanged) response, and
+    /// navigation requests can be sent to the other tabs using
+    /// [NavigateOtherTabs](enum.RouteRequest.html#variant.NavigateOtherTabs).
+    /// Route changes caused by another tab aren't announced back to the other tabs.
+    SetTabSync(Option<String>),
+    /// Asks the other tabs to change their route, like `ChangeRoute` would.
+    ///
+    /// The other tabs don't ask their users to confirm the navigation, instead guards using
+    /// [Confirm](enum.GuardCheck.html#variant.Confirm) veto it.
+    ///
+    /// This does nothing unless tab synchronization has been enabled using
+    /// [SetTabSync](enum.RouteRequest.html#variant.SetTabSync).
+    NavigateOtherTabs(Route<T>),
+    /// Enables persisting the state of history entries in web storage, or disables it if `None`
+    /// is provided.
+    ///
+    /// While enabled, the state of entries that the browser has lost, for example after a crash,
+    /// is restored from the storage.
+    /// If the state of the current route is restored when this is enabled, every subscriber is
+    /// sent the restored route.
+    SetStatePersistence(Option<StatePersistence>),
+    /// Limits which route changes are sent to the entity, which is sent every route change by
+    /// default.
+    SetInterest(RouteInterest),
+    /// Removes the entity from the Router Agent.
+    ///
+    /// This is sent automatically when a
+    /// [RouteAgentBridge](struct.RouteAgentBridge.html) is dropped, so it only needs to be sent
+    /// by entities using a bridge to the agent directly.
     Disconnect,
 }
 
+/// Output message type of the `RouteAgent`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RouteResponse<T> {
+    /// The route has changed, or the current route was requested.
+    RouteChanged(RouteChange<T>),
+    /// The route of another tab has changed.
+    OtherTabRouteChanged(Route<T>),
+    /// A navigation is waiting on a decision from this entity's
+    /// [Deferred](enum.GuardCheck.html#variant.Deferred) guard.
+    ConfirmNavigation(PendingNavigation<T>),
+}
+
+/// A route sent to subscribers of the `RouteAgent`, along with how it was reached.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RouteChange<T> {
+    /// The new route.
+    pub route: Route<T>,
+    /// How the route was reached.
+    pub kind: NavigationKind,
+    /// What started the navigation.
+    pub source: NavigationSource,
+    /// The position of the route's history entry in the history of the app, which starts at `0`
+    /// for the entry the app was loaded with.
+    ///
+    /// The index is stored in the history entry, so it survives reloads of the page.
+    pub index: u32,
+    /// The index of the history entry that was current before the navigation.
+    pub previous_index: u32,
+    /// Why the state of the route's history entry was dropped, in which case the state of the
+    /// route is `None`.
+    pub state_error: Option<StateError>,
+}
+
+impl<T> RouteChange<T> {
+    /// Checks if there is an entry of this app to go back to in the browser's history.
+    pub fn can_go_back(&self) -> bool {
+        self.index > 0
+    }
+
+    /// Checks if the navigation moved back in the browser's history.
+    pub fn went_back(&self) -> bool {
+        self.index < self.previous_index
+    }
+}
+
+/// What started a navigation.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavigationSource {
+    /// A [RouteRequest](enum.RouteRequest.html) was sent to the agent.
+    Programmatic,
+    /// The browser's back or forward button was used, or an intercepted anchor tag was clicked.
+    Browser,
+    /// Another tab asked for the navigation.
+    OtherTab,
+}
+
+/// How the current route was reached.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NavigationKind {
+    /// No navigation took place, the current route was requested.
+    Current,
+    /// A new history entry was created.
+    Push,
+    /// The current history entry was replaced.
+    Replace,
+    /// The browser moved to a different history entry, using its back or forward buttons.
+    ///
+    /// Whether it moved back can be told by comparing the indexes of the entries.
+    Pop,
+}
+
+/// How a navigation is applied once every guard has allowed it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NavigationAction {
+    /// Create a new history entry.
+    Push { broadcast: bool },
+    /// Replace the current history entry.
+    Replace { broadcast: bool },
+    /// The browser has already moved to a different history entry.
+    Pop,
+}
+
+impl NavigationAction {
+    /// The kind of navigation reported to subscribers.
+    fn kind(self) -> NavigationKind {
+        match self {
+            NavigationAction::Push { .. } => NavigationKind::Push,
+            NavigationAction::Replace { .. } => NavigationKind::Replace,
+            NavigationAction::Pop => NavigationKind::Pop,
+        }
+    }
+
+    /// The action used to navigate to a route a guard redirected to.
+    fn redirected(self) -> Self {
+        match self {
+            NavigationAction::Pop => NavigationAction::Replace { broadcast: true },
+            action => action,
+        }
+    }
+}
+
+/// A navigation that was requested while a vetoed browser navigation was being undone.
+#[derive(Debug)]
+struct Queued<T> {
+    to: Route<T>,
+    action: NavigationAction,
+    source: NavigationSource,
+    redirects: usize,
+}
+
+/// A navigation waiting on deferred guards.
+#[derive(Debug)]
+struct Pending<T> {
+    id: u32,
+    to: Route<T>,
+    action: NavigationAction,
+    source: NavigationSource,
+    awaiting: HashSet<HandlerId>,
+    redirects: usize,
+}
+
+/// Guards can redirect to routes which are guarded themselves, this limits how often that may
+/// happen for a single navigation.
+const MAX_REDIRECTS: usize = 8;
+
 /// The RouteAgent holds on to the RouteService singleton and mediates access to it.
 ///
 /// It serves as a means to propagate messages to components interested in the state of the current
 /// route.
 ///
+/// # Guards
+/// Before any navigation is applied, the guards registered with
+/// [RegisterGuard](enum.RouteRequest.html#variant.RegisterGuard) that apply to it are consulted.
+/// Guards using [Confirm](enum.GuardCheck.html#variant.Confirm) are checked immediately, then
+/// guards using [Deferred](enum.GuardCheck.html#variant.Deferred) are asked, and the navigation
+/// is only applied once all of them have allowed it.
+/// If a browser back or forward navigation is vetoed, the browser is moved back to the entry of
+/// the previous route, leaving the rest of its history untouched.
+///
 /// # Warning
 /// All routing-related components should use the same type parameter across your application.
 ///
@@ -79,6 +289,26 @@ where
     /// When a route changes, either initiated by the browser or by the app,
     /// the route change will be broadcast to all listening entities.
     subscribers: HashSet<HandlerId>,
+    /// The interest of subscribers that don't want to be sent every route change.
+    interests: HashMap<HandlerId, RouteInterest>,
+    /// The route that was last applied.
+    current_route: Route<T>,
+    /// The index of the current history entry.
+    index: u32,
+    /// Registered guards, in the order of their registration.
+    guards: Vec<(HandlerId, NavigationGuard)>,
+    /// The navigation waiting on deferred guards, if any.
+    pending: Option<Pending<T>>,
+    next_navigation_id: u32,
+    /// Whether a vetoed browser navigation is being undone, in which case the `popstate` event
+    /// that follows is ignored.
+    restoring: bool,
+    /// The navigation to apply once the vetoed browser navigation has been undone.
+    queued: Option<Queued<T>>,
+    /// Whether the agent manages the scroll position.
+    scroll_restoration: bool,
+    /// Why the state of the current route was dropped.
+    state_error: Option<StateError>,
 }
 
 impl<T: for<'de> AgentState<'de>> Debug for RouteAgent<T> {
@@ -87,40 +317,487 @@ impl<T: for<'de> AgentState<'de>> Debug for RouteAgent<T> {
             .field("link", &"-")
             .field("route_service", &self.route_service)
             .field("subscribers", &self.subscribers.len())
+            .field("interests", &self.interests)
+            .field("current_route", &self.current_route)
+            .field("index", &self.index)
+            .field("guards", &self.guards.len())
+            .field("pending", &self.pending)
+            .field("restoring", &self.restoring)
+            .field("queued", &self.queued)
+            .field("scroll_restoration", &self.scroll_restoration)
+            .field("state_error", &self.state_error)
             .finish()
     }
 }
 
+impl<T> RouteAgent<T>
+where
+    for<'de> T: AgentState<'de>,
+{
+    /// Sends the change to every subscriber interested in the navigation from the previous route.
+    fn broadcast(&self, change: &RouteChange<T>, previous_route: &Route<T>) {
+        for sub in &self.subscribers {
+            if self.is_interested(*sub, previous_route, &change.route) {
+                self.link
+                    .response(*sub, RouteResponse::RouteChanged(change.clone()));
+            }
+        }
+    }
+
+    /// Checks if the subscriber wants to be told about the navigation between the routes.
+    fn is_interested(&self, sub: HandlerId, from: &Route<T>, to: &Route<T>) -> bool {
+        self.interests
+            .get(&sub)
+            .map(|interest| interest.applies_to(from, to))
+            .unwrap_or(true)
+    }
+
+    /// Handles a message sent by another tab.
+    fn receive_from_tab(&mut self, message: TabMessage<T>) {
+        match message {
+            TabMessage::RouteChanged(route, state) => {
+                let route = Route { route, state };
+                for sub in &self.subscribers {
+                    if self.is_interested(*sub, &route, &route) {
+                        self.link
+                            .response(*sub, RouteResponse::OtherTabRouteChanged(route.clone()));
+                    }
+                }
+            }
+            TabMessage::Navigate(route, state) => {
+                self.navigate(
+                    Route { route, state },
+                    NavigationAction::Push { broadcast: true },
+                    NavigationSource::OtherTab,
+                    0,
+                );
+            }
+        }
+    }
+
+    /// Consults the guards before applying a navigation.
+    ///
+    /// Relative routes are resolved against the current route.
+    /// Any navigation that is still waiting on guards is abandoned.
+    /// If that navigation was a browser navigation, the browser is moved back first, and the new
+    /// navigation is applied afterwards.
+    fn navigate(
+        &mut self,
+        to: Route<T>,
+        action: NavigationAction,
+        source: NavigationSource,
+        redirects: usize,
+    ) {
+        if let Some(pending) = self.pending.take() {
+            // Another browser navigation has already moved the browser on by itself.
+            if pending.action == NavigationAction::Pop && action != NavigationAction::Pop {
+                self.reject(pending.action);
+            }
+        }
+        if self.restoring {
+            self.queued = Some(Queued {
+                to,
+                action,
+                source,
+                redirects,
+            });
+            return;
+        }
+
+        let to = self.current_route.resolve_route(to);
+        let id = self.next_navigation_id;
+        self.next_navigation_id = self.next_navigation_id.wrapping_add(1);
+
+        let applicable: Vec<(HandlerId, NavigationGuard)> = self
+            .guards
+            .iter()
+            .filter(|(_, guard)| guard.applies_to(&self.current_route, &to))
+            .cloned()
+            .collect();
+
+        let confirmed = applicable.iter().all(|(_, guard)| match &guard.check {
+            // The user isn't asked to confirm navigation from another tab, as this tab may not
+            // even be visible.
+            GuardCheck::Confirm(_) if source == NavigationSource::OtherTab => false,
+            GuardCheck::Confirm(message) => guard::confirm(message),
+            GuardCheck::Deferred => true,
+        });
+        if !confirmed {
+            trace!("Navigation to {} was vetoed", to.route);
+            self.reject(action);
+            return;
+        }
+
+        let mut awaiting = HashSet::new();
+        for (handler, guard) in applicable {
+            if let GuardCheck::Deferred = guard.check {
+                let pending = PendingNavigation {
+                    id,
+                    from: self.current_route.clone(),
+                    to: to.clone(),
+                    hook: guard.hook,
+                };
+                self.link
+                    .response(handler, RouteResponse::ConfirmNavigation(pending));
+                awaiting.insert(handler);
+            }
+        }
+
+        if awaiting.is_empty() {
+            self.apply(to, action, source);
+        } else {
+            self.pending = Some(Pending {
+                id,
+                to,
+                action,
+                source,
+                awaiting,
+                redirects,
+            });
+        }
+    }
+
+    /// Navigates to a route that was requested by a connected entity.
+    fn request(&mut self, to: Route<T>, action: NavigationAction) {
+        self.navigate(to, action, NavigationSource::Programmatic, 0);
+    }
+
+    /// Applies a navigation that every guard has allowed.
+    fn apply(&mut self, to: Route<T>, action: NavigationAction, source: NavigationSource) {
+        if self.scroll_restoration {
+            // The position is tracked while scrolling, this stores it in case that hasn't
+            // happened yet.
+            if let NavigationAction::Push { .. } = action {
+                self.route_service.store_scroll_position();
+            }
+        }
+        let previous_index = self.index;
+        let route_string: String = to.to_string();
+        let entry = HistoryEntry {
+            index: Some(self.index),
+            ..HistoryEntry::from(to.state.unwrap_or_default())
+        };
+        let broadcast = match action {
+            NavigationAction::Push { broadcast } => {
+                self.index = self.index.saturating_add(1);
+                let entry = HistoryEntry {
+                    index: Some(self.index),
+                    ..entry
+                };
+                self.route_service.set_entry(&route_string, &entry);
+                broadcast
+            }
+            NavigationAction::Replace { broadcast } => {
+                // The page isn't scrolled when the route is replaced, so the new entry takes over
+                // the position.
+                let scroll = if self.scroll_restoration {
+                    Some(self.route_service.get_scroll_position())
+                } else {
+                    None
+                };
+                let entry = HistoryEntry { scroll, ..entry };
+                self.route_service.replace_entry(&route_string, &entry);
+                broadcast
+            }
+            NavigationAction::Pop => {
+                self.index = self.popped_index();
+                self.route_service.remember_entry();
+                true
+            }
+        };
+        // get the new route, along with the state that was just stored
+        let route = self.read_current_route();
+        let previous_route = std::mem::replace(&mut self.current_route, route);
+        if self.scroll_restoration {
+            self.restore_scroll(action);
+        }
+        if posts_to_tabs(broadcast, source) {
+            self.route_service.post_to_tabs(&TabMessage::RouteChanged(
+                self.current_route.route.clone(),
+                self.current_route.state.clone(),
+            ));
+        }
+        if broadcast {
+            let change = RouteChange {
+                route: self.current_route.clone(),
+                kind: action.kind(),
+                source,
+                index: self.index,
+                previous_index,
+                state_error: self.state_error.clone(),
+            };
+            self.broadcast(&change, &previous_route);
+        }
+    }
+
+    /// Reads the current route, keeping the error that made its state be dropped.
+    fn read_current_route(&mut self) -> Route<T> {
+        let route = self.route_service.get_route();
+        let (route, error) = split_state(route, self.route_service.try_get_state());
+        if let Some(error) = &error {
+            warn!("The state of the history entry was dropped: {}", error);
+        }
+        self.state_error = error;
+        route
+    }
+
+    /// Reads the index of the entry the browser moved to.
+    ///
+    /// Entries that weren't created by the agent, like ones created by following a link to a
+    /// fragment, are given the index following the current one.
+    /// The index is only stored in entries whose state wouldn't be lost by replacing them.
+    fn popped_index(&mut self) -> u32 {
+        let entry = self.route_service.get_entry();
+        match entry.index {
+            Some(index) => index,
+            None => {
+                let index = self.index.saturating_add(1);
+                if self.route_service.can_replace_entry() {
+                    let route = self.route_service.get_route();
+                    let entry = HistoryEntry {
+                        index: Some(index),
+                        ..entry
+                    };
+                    self.route_service.replace_entry(&route, &entry);
+                }
+                index
+            }
+        }
+    }
+
+    /// Scrolls the page after a navigation has been applied.
+    fn restore_scroll(&self, action: NavigationAction) {
+        let fragment = self.route_service.get_fragment();
+        let stored = match action {
+            NavigationAction::Pop => self.route_service.get_entry().scroll,
+            _ => None,
+        };
+        match scroll_target(action, stored, !fragment.is_empty()) {
+            Some(ScrollTarget::Position(position)) => self.route_service.scroll_to(position),
+            Some(ScrollTarget::Fragment) => self.route_service.scroll_to_fragment(&fragment),
+            None => {}
+        }
+    }
+
+    /// Cancels a navigation.
+    fn reject(&mut self, action: NavigationAction) {
+        if let NavigationAction::Pop = action {
+            // The browser has already moved to a different entry, so it is moved back to the entry
+            // of the current route.
+            let popped = self.route_service.get_entry().index;
+            let delta = restore_delta(self.index, popped);
+            if delta != 0 {
+                self.restoring = true;
+                self.route_service.go(delta);
+            }
+        }
+    }
+
+    /// Handles the `popstate` event caused by undoing a vetoed browser navigation, applying the
+    /// navigation that was requested in the meantime.
+    fn restored(&mut self) {
+        self.restoring = false;
+        if let Some(queued) = self.queued.take() {
+            self.navigate(queued.to, queued.action, queued.source, queued.redirects);
+        }
+    }
+
+    /// Handles a guard's answer to the pending navigation.
+    fn resolve(&mut self, id: u32, decision: GuardDecision<T>, who: HandlerId) {
+        let is_awaited = match &mut self.pending {
+            Some(pending) if pending.id == id => pending.awaiting.remove(&who),
+            _ => false,
+        };
+        if !is_awaited {
+            trace!("Ignoring decision for stale navigation {}", id);
+            return;
+        }
+
+        match decision {
+            GuardDecision::Allow => self.apply_if_allowed(),
+            GuardDecision::Deny => {
+                if let Some(pending) = self.pending.take() {
+                    self.reject(pending.action);
+                }
+            }
+            GuardDecision::Redirect(route) => {
+                if let Some(pending) = self.pending.take() {
+                    if pending.redirects < MAX_REDIRECTS {
+                        self.navigate(
+                            route,
+                            pending.action.redirected(),
+                            pending.source,
+                            pending.redirects + 1,
+                        );
+                    } else {
+                        warn!("Too many guard redirects, navigation was cancelled");
+                        self.reject(pending.action);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Applies the pending navigation once no more guards are awaited.
+    fn apply_if_allowed(&mut self) {
+        let allowed = self
+            .pending
+            .as_ref()
+            .map(|pending| pending.awaiting.is_empty())
+            .unwrap_or(false);
+        if allowed {
+            if let Some(pending) = self.pending.take() {
+                self.apply(pending.to, pending.action, pending.source);
+            }
+        }
+    }
+}
+
+/// Splits the result of reading the state of the route into the route, which has no state if it
+/// couldn't be read, and the error.
+fn split_state<T>(
+    route: String,
+    state: Result<Option<T>, StateError>,
+) -> (Route<T>, Option<StateError>) {
+    match state {
+        Ok(state) => (Route { route, state }, None),
+        Err(error) => (Route { route, state: None }, Some(error)),
+    }
+}
+
+/// Whether other tabs are told about a navigation.
+///
+/// Navigation that isn't broadcast stays hidden from other tabs too, and navigation another tab
+/// asked for isn't reported back to it.
+fn posts_to_tabs(broadcast: bool, source: NavigationSource) -> bool {
+    broadcast && source != NavigationSource::OtherTab
+}
+
+/// Where the page is scrolled to after a navigation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScrollTarget {
+    Position(ScrollPosition),
+    /// The element whose id matches the fragment of the route.
+    Fragment,
+}
+
+/// Determines where the page is scrolled to after a navigation, given the scroll position stored
+/// in the entry that was navigated to.
+///
+/// Entries the browser moved back or forward to are scrolled to their stored position, otherwise
+/// the fragment is scrolled into view, and new entries without a fragment are scrolled to the top.
+fn scroll_target(
+    action: NavigationAction,
+    stored: Option<ScrollPosition>,
+    has_fragment: bool,
+) -> Option<ScrollTarget> {
+    match (action, stored) {
+        (NavigationAction::Pop, Some(position)) => Some(ScrollTarget::Position(position)),
+        _ if has_fragment => Some(ScrollTarget::Fragment),
+        (NavigationAction::Push { .. }, _) => {
+            Some(ScrollTarget::Position(ScrollPosition::default()))
+        }
+        _ => None,
+    }
+}
+
+/// The number of entries to move through the browser's history to get back to the entry with the
+/// current index, after the browser has moved to the popped entry.
+///
+/// Entries without an index were not created by the agent, like ones created by following a link
+/// to a fragment, so the browser is assumed to have moved forward onto them.
+fn restore_delta(current: u32, popped: Option<u32>) -> i32 {
+    match popped {
+        Some(popped) => current as i32 - popped as i32,
+        None => -1,
+    }
+}
+
+/// Reads the index of the entry the app was loaded with, marking it as the first entry if it
+/// doesn't have one yet.
+///
+/// Like in `popped_index`, entries with state written by other scripts aren't marked.
+fn initial_index<T: RouteState>(route_service: &mut RouteService<T>) -> u32 {
+    let entry = route_service.get_entry();
+    match entry.index {
+        Some(index) => index,
+        None => {
+            if route_service.can_replace_entry() {
+                let route = route_service.get_route();
+                let entry = HistoryEntry {
+                    index: Some(0),
+                    ..entry
+                };
+                route_service.replace_entry(&route, &entry);
+            }
+            0
+        }
+    }
+}
+
 impl<T> Agent for RouteAgent<T>
 where
     for<'de> T: AgentState<'de>,
 {
     type Input = RouteRequest<T>;
     type Message = Msg<T>;
-    type Output = Route<T>;
+    type Output = RouteResponse<T>;
     type Reach = Context;
 
     fn create(link: AgentLink<RouteAgent<T>>) -> Self {
         let callback = link.send_back(Msg::BrowserNavigationRouteChanged);
         let mut route_service = RouteService::new();
         route_service.register_callback(callback);
+        let (current_route, state_error) =
+            split_state(route_service.get_route(), route_service.try_get_state());
+        let index = initial_index(&mut route_service);
 
         RouteAgent {
             link,
             route_service,
             subscribers: HashSet::new(),
+            interests: HashMap::new(),
+            current_route,
+            index,
+            guards: Vec::new(),
+            pending: None,
+            next_navigation_id: 0,
+            restoring: false,
+            queued: None,
+            scroll_restoration: false,
+            state_error,
         }
     }
 
     fn update(&mut self, msg: Self::Message) {
         match msg {
-            Msg::BrowserNavigationRouteChanged((_route_string, state)) => {
+            Msg::BrowserNavigationRouteChanged(_) if self.restoring => {
+                trace!("Vetoed browser navigation was undone");
+                self.restored();
+            }
+            Msg::BrowserNavigationRouteChanged(_) => {
                 trace!("Browser navigated");
-                let mut route = Route::current_route(&self.route_service);
-                route.state = Some(state);
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
-                }
+                // The state is read back from the history entry, so entries without state are
+                // reported as `None` instead of a default value, and state that can't be decoded
+                // is reported to subscribers once the navigation is applied.
+                let route = Route::current_route(&self.route_service);
+                self.navigate(route, NavigationAction::Pop, NavigationSource::Browser, 0);
+            }
+            Msg::AnchorClicked(ref route) if *route == self.current_route.route => {
+                trace!("Anchor to the current route clicked: {}", route);
+            }
+            Msg::AnchorClicked(route) => {
+                trace!("Anchor clicked: {}", route);
+                self.navigate(
+                    Route::from(route),
+                    NavigationAction::Push { broadcast: true },
+                    NavigationSource::Browser,
+                    0,
+                );
+            }
+            Msg::TabMessageReceived(message) => {
+                trace!("Received message from another tab: {:?}", message);
+                self.receive_from_tab(message);
             }
         }
     }
@@ -132,39 +809,101 @@ where
     fn handle(&mut self, msg: Self::Input, who: HandlerId) {
         match msg {
             RouteRequest::ReplaceRoute(route) => {
-                let route_string: String = route.to_string();
-                self.route_service
-                    .replace_route(&route_string, route.state.unwrap_or_default());
-                let route = Route::current_route(&self.route_service);
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
-                }
+                self.request(route, NavigationAction::Replace { broadcast: true });
             }
             RouteRequest::ReplaceRouteNoBroadcast(route) => {
-                let route_string: String = route.to_string();
-                self.route_service
-                    .replace_route(&route_string, route.state.unwrap_or_default());
+                self.request(route, NavigationAction::Replace { broadcast: false });
             }
             RouteRequest::ChangeRoute(route) => {
-                let route_string: String = route.to_string();
-                // set the route
-                self.route_service
-                    .set_route(&route_string, route.state.unwrap_or_default());
-                // get the new route. This will contain a default state object
-                let route = Route::current_route(&self.route_service);
-                // broadcast it to all listening components
-                for sub in &self.subscribers {
-                    self.link.response(*sub, route.clone());
-                }
+                self.request(route, NavigationAction::Push { broadcast: true });
             }
             RouteRequest::ChangeRouteNoBroadcast(route) => {
-                let route_string: String = route.to_string();
-                self.route_service
-                    .set_route(&route_string, route.state.unwrap_or_default());
+                self.request(route, NavigationAction::Push { broadcast: false });
+            }
+            RouteRequest::UpdateQuery(changes) => {
+                let mut route = Route::current_route(&self.route_service);
+                route.update_query(&changes);
+                self.request(route, NavigationAction::Push { broadcast: true });
+            }
+            RouteRequest::ReplaceQuery(changes) => {
+                let mut route = Route::current_route(&self.route_service);
+                route.update_query(&changes);
+                self.request(route, NavigationAction::Replace { broadcast: true });
             }
             RouteRequest::GetCurrentRoute => {
-                let route = Route::current_route(&self.route_service);
-                self.link.response(who, route.clone());
+                let change = RouteChange {
+                    route: self.read_current_route(),
+                    kind: NavigationKind::Current,
+                    source: NavigationSource::Programmatic,
+                    index: self.index,
+                    previous_index: self.index,
+                    state_error: self.state_error.clone(),
+                };
+                self.link.response(who, RouteResponse::RouteChanged(change));
+            }
+            RouteRequest::RegisterGuard(guard) => {
+                self.guards.retain(|(handler, _)| *handler != who);
+                self.guards.push((who, guard));
+            }
+            RouteRequest::UnregisterGuard => {
+                self.guards.retain(|(handler, _)| *handler != who);
+            }
+            RouteRequest::ResolveNavigation(id, decision) => {
+                self.resolve(id, decision, who);
+            }
+            RouteRequest::SetScrollRestoration(enabled) => {
+                self.scroll_restoration = enabled;
+                self.route_service.set_browser_scroll_restoration(!enabled);
+                if enabled {
+                    self.route_service.track_scroll_position();
+                } else {
+                    self.route_service.stop_tracking_scroll_position();
+                }
+            }
+            RouteRequest::InterceptAnchorClicks(enabled) => {
+                if enabled {
+                    let callback = self.link.send_back(Msg::AnchorClicked);
+                    self.route_service.intercept_anchor_clicks(callback);
+                } else {
+                    self.route_service.stop_intercepting_anchor_clicks();
+                }
+            }
+            RouteRequest::SetTabSync(Some(channel)) => {
+                let callback = self.link.send_back(Msg::TabMessageReceived);
+                self.route_service.open_tab_channel(&channel, callback);
+            }
+            RouteRequest::SetTabSync(None) => {
+                self.route_service.close_tab_channel();
+            }
+            RouteRequest::NavigateOtherTabs(route) => {
+                let route = self.current_route.resolve_route(route);
+                self.route_service
+                    .post_to_tabs(&TabMessage::Navigate(route.route, route.state));
+            }
+            RouteRequest::SetStatePersistence(persistence) => {
+                self.route_service.set_persistence(persistence);
+                let route = self.read_current_route();
+                let restored = !RouteService::is_unset(&route.state)
+                    && RouteService::is_unset(&self.current_route.state);
+                if restored {
+                    let previous_route = std::mem::replace(&mut self.current_route, route.clone());
+                    let change = RouteChange {
+                        route,
+                        kind: NavigationKind::Current,
+                        source: NavigationSource::Programmatic,
+                        index: self.index,
+                        previous_index: self.index,
+                        state_error: None,
+                    };
+                    self.broadcast(&change, &previous_route);
+                }
+            }
+            RouteRequest::SetInterest(interest) => {
+                if interest == RouteInterest::All {
+                    self.interests.remove(&who);
+                } else {
+                    self.interests.insert(who, interest);
+                }
             }
             RouteRequest::Disconnect => {
                 self.disconnected(who);
@@ -174,5 +913,101 @@ where
 
     fn disconnected(&mut self, id: HandlerId) {
         self.subscribers.remove(&id);
+        self.interests.remove(&id);
+        self.guards.retain(|(handler, _)| *handler != id);
+        // A guard that goes away can't veto the navigation it was asked about anymore.
+        if let Some(pending) = &mut self.pending {
+            if pending.awaiting.remove(&id) {
+                self.apply_if_allowed();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vetoed_navigation_is_undone_relative_to_popped_entry() {
+        assert_eq!(restore_delta(3, Some(2)), 1);
+        assert_eq!(restore_delta(3, Some(5)), -2);
+        assert_eq!(restore_delta(3, None), -1);
+        assert_eq!(restore_delta(3, Some(3)), 0);
+    }
+
+    #[test]
+    fn popped_entries_are_scrolled_to_their_position() {
+        let position = ScrollPosition { x: 0.0, y: 120.0 };
+        let pop = NavigationAction::Pop;
+        assert_eq!(
+            scroll_target(pop, Some(position), true),
+            Some(ScrollTarget::Position(position))
+        );
+        assert_eq!(scroll_target(pop, None, true), Some(ScrollTarget::Fragment));
+        assert_eq!(scroll_target(pop, None, false), None);
+    }
+
+    #[test]
+    fn new_entries_are_scrolled_to_fragment_or_top() {
+        let push = NavigationAction::Push { broadcast: true };
+        let replace = NavigationAction::Replace { broadcast: true };
+        assert_eq!(
+            scroll_target(push, None, true),
+            Some(ScrollTarget::Fragment)
+        );
+        assert_eq!(
+            scroll_target(push, None, false),
+            Some(ScrollTarget::Position(ScrollPosition::default()))
+        );
+        assert_eq!(scroll_target(replace, None, false), None);
+    }
+
+    #[test]
+    fn redirects_replace_popped_entries() {
+        assert_eq!(
+            NavigationAction::Pop.redirected(),
+            NavigationAction::Replace { broadcast: true }
+        );
+        let push = NavigationAction::Push { broadcast: false };
+        assert_eq!(push.redirected(), push);
+    }
+
+    #[test]
+    fn state_errors_are_kept_with_the_route() {
+        let (route, error) = split_state("/a".to_string(), Ok(Some(3)));
+        assert_eq!(route.state, Some(3));
+        assert_eq!(error, None);
+
+        let error = StateError::UnsupportedVersion(2);
+        let (route, kept) = split_state::<u32>("/a".to_string(), Err(error.clone()));
+        assert_eq!(route.route, "/a");
+        assert_eq!(route.state, None);
+        assert_eq!(kept, Some(error));
+    }
+
+    #[test]
+    fn state_errors_are_sent_to_subscribers() {
+        let change = RouteChange::<u32> {
+            route: Route::from("/a"),
+            kind: NavigationKind::Pop,
+            source: NavigationSource::Browser,
+            index: 1,
+            previous_index: 2,
+            state_error: Some(StateError::Decode("invalid type".to_string())),
+        };
+        let json = serde_json::to_string(&RouteResponse::RouteChanged(change.clone())).unwrap();
+        match serde_json::from_str(&json).unwrap() {
+            RouteResponse::RouteChanged(received) => assert_eq!(received, change),
+            other => panic!("expected a route change, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn only_local_broadcast_navigation_is_posted_to_tabs() {
+        assert!(posts_to_tabs(true, NavigationSource::Programmatic));
+        assert!(posts_to_tabs(true, NavigationSource::Browser));
+        assert!(!posts_to_tabs(false, NavigationSource::Programmatic));
+        assert!(!posts_to_tabs(true, NavigationSource::OtherTab));
     }
 }
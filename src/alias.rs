@@ -59,19 +59,25 @@ macro_rules! define_router_state {
             #[doc = "Alias to [RouterLink<"]
             #[doc = $StateName]
             #[doc = ">](components/struct.RouterLink.html)`."]
-            pub type RouterLink = $crate::components::RouterLink<$StateT>;
+            pub type RouterLink<SW = String> = $crate::components::RouterLink<$StateT, SW>;
 
             #[cfg(feature="components")]
             #[doc = "Alias to [RouterButton<"]
             #[doc = $StateName]
             #[doc = ">](components/struct.RouterButton.html)`."]
-            pub type RouterButton = $crate::components::RouterButton<$StateT>;
+            pub type RouterButton<SW = String> = $crate::components::RouterButton<$StateT, SW>;
+
+            #[cfg(feature="components")]
+            #[doc = "Alias to [Redirect<"]
+            #[doc = $StateName]
+            #[doc = ">](components/struct.Redirect.html)`."]
+            pub type Redirect<SW = String> = $crate::components::Redirect<$StateT, SW>;
 
             #[cfg(feature="router")]
             #[doc = "Alias to [Router<"]
             #[doc = $StateName]
             #[doc = ">](router/router/struct.Router.html)."]
-            pub type Router<SW, M> = $crate::router::Router<$StateT, SW, M>;
+            pub type Router<SW, M, D = (), E = ()> = $crate::router::Router<$StateT, SW, M, D, E>;
 
         }
     }
@@ -1,31 +1,367 @@
 //! Components that integrate with the [route agent](struct.RouteAgent.html).
 
+mod redirect;
 mod router_button;
 mod router_link;
 
-use yew::Properties;
+use yew::{html::Children, virtual_dom::VNode, Component, ComponentLink, Html, Properties};
 
-pub use self::{router_button::RouterButton, router_link::RouterLink};
-use crate::RouterState;
+pub use self::{
+    redirect::{Redirect, RedirectProps},
+    router_button::RouterButton,
+    router_link::RouterLink,
+};
+use crate::agent::{RouteAgentBridge, RouteRequest};
+use crate::{
+    route::{has_prefix, Route, RouteScope},
+    switch::build_route_from_switch,
+    RouterState, Switch,
+};
 
 /// Properties for `RouterButton` and `RouterLink`.
-#[derive(Properties, Default, Clone, Debug, PartialEq)]
-pub struct Props<T: for<'de> RouterState<'de>> {
+///
+/// The link can either be a route string, or any other type implementing `Switch`, which allows
+/// links to be checked against a route enum.
+///
+/// The `COMP` is the component the properties belong to, which is needed for its children.
+#[derive(Properties, Debug)]
+pub struct Props<T, SW, COMP>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    COMP: Component,
+{
     /// The route that will be set when the component is clicked.
-    pub link: String,
+    ///
+    /// Without a link, the empty route is set, like with an empty `String` link.
+    pub link: Option<SW>,
     /// The state to set when changing the route.
     pub state: Option<T>,
     /// The text to display.
+    /// It is displayed before any children.
     pub text: String,
     /// Disable the component.
     pub disabled: bool,
     /// Classes to be added to component.
     pub classes: String,
+    /// Where to display the linked route, like the `target` attribute of an anchor tag.
+    ///
+    /// Only `RouterLink` makes use of this.
+    /// If it is set to anything other than `_self`, navigation is left to the browser.
+    pub target: String,
+    /// Elements to display inside of the component.
+    pub children: Children<COMP>,
+    /// Classes to be added to the component when its route is the current route.
+    ///
+    /// If this is set, the component will subscribe to route changes.
+    pub active_class: String,
+    /// How the current route is compared to the component's route to determine if it is active.
+    pub active_match: ActiveMatch,
+    /// Replace the current route instead of creating a new history entry when clicked.
+    pub replace: bool,
+    /// The id of the element.
+    pub id: String,
+    /// The title of the element.
+    pub title: String,
+    /// Any other attributes to set on the element, such as `aria-*` or `data-*` attributes.
+    pub attributes: Vec<(String, String)>,
+    /// The link is relative to this scope.
+    pub scope: RouteScope,
+}
+
+impl<T, SW, COMP> Default for Props<T, SW, COMP>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    COMP: Component,
+{
+    fn default() -> Self {
+        Props {
+            link: None,
+            state: None,
+            text: String::new(),
+            disabled: false,
+            classes: String::new(),
+            target: String::new(),
+            children: Children::default(),
+            active_class: String::new(),
+            active_match: ActiveMatch::default(),
+            replace: false,
+            id: String::new(),
+            title: String::new(),
+            attributes: Vec::new(),
+            scope: RouteScope::default(),
+        }
+    }
+}
+
+/// Determines how the current route is compared to a component's route to determine if the
+/// component is active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ActiveMatch {
+    /// The route strings must be equal.
+    Exact,
+    /// The current route must start with the component's route, up to a path separator, query, or
+    /// fragment.
+    Prefix,
+    /// The current route must produce a `Switch` that builds the same route as the component's
+    /// link.
+    Switch,
+}
+
+impl Default for ActiveMatch {
+    fn default() -> Self {
+        ActiveMatch::Exact
+    }
+}
+
+/// The route a `RouterButton` or `RouterLink` links to, built from its properties.
+#[derive(Debug)]
+pub(crate) struct Target<T> {
+    /// The route that is set when the component is clicked.
+    pub(crate) route: Route<T>,
+    /// The route built from the link, which is compared to the route built from the switch that
+    /// matches the current route.
+    link: Option<String>,
 }
 
 /// Message for `RouterButton` and `RouterLink`.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Msg {
     /// Tell the router to navigate the application to the Component's pre-defined route.
     Clicked,
+    /// A click that doesn't cause the router to navigate.
+    Ignored,
+    /// The current route has changed.
+    RouteChanged(String),
+}
+
+impl<T, SW, COMP> Props<T, SW, COMP>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    COMP: Component,
+{
+    /// Takes the link out of the properties, building the route the component links to.
+    ///
+    /// Building the route consumes the link, so this is done once whenever the properties are set.
+    pub(crate) fn take_target(&mut self) -> Target<T> {
+        let link: Option<Route<T>> = self.link.take().map(build_route_from_switch);
+        let (route, state) = match &link {
+            Some(link) => (link.route.as_str(), link.state.clone()),
+            None => ("", None),
+        };
+        let route = Route {
+            route: self.scope.join(route),
+            state: self.state.clone().or(state),
+        };
+        Target {
+            route,
+            link: link.map(|link| link.route),
+        }
+    }
+
+    /// The request sent to the agent when the component is clicked.
+    pub(crate) fn request(&self, target: &Target<T>) -> RouteRequest<T> {
+        let route = target.route.clone();
+        if self.replace {
+            RouteRequest::ReplaceRoute(route)
+        } else {
+            RouteRequest::ChangeRoute(route)
+        }
+    }
+
+    /// Sets the optional attributes on the element rendered by the component.
+    ///
+    /// Active components are marked using `aria-current="page"`.
+    pub(crate) fn set_attributes(&self, html: &mut Html<COMP>, active: bool) {
+        if let VNode::VTag(tag) = html {
+            if active {
+                tag.add_attribute("aria-current", &"page");
+            }
+            if !self.id.is_empty() {
+                tag.add_attribute("id", &self.id);
+            }
+            if !self.title.is_empty() {
+                tag.add_attribute("title", &self.title);
+            }
+            for (name, value) in &self.attributes {
+                tag.add_attribute(name, value);
+            }
+        }
+    }
+}
+
+impl<T, SW, COMP> Props<T, SW, COMP>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    COMP: Component,
+{
+    /// Determines if the component linking to the target is active for the current route.
+    pub(crate) fn is_active(&self, target: &Target<T>, current_route: &str) -> bool {
+        // Relative links are resolved against the current route.
+        let route = Route::<T>::from(current_route).resolve(&target.route.route);
+        match self.active_match {
+            ActiveMatch::Exact => current_route == route,
+            ActiveMatch::Prefix => has_prefix(current_route, &route),
+            ActiveMatch::Switch => {
+                let matched = self
+                    .scope
+                    .strip(Route::<T>::from(current_route))
+                    .and_then(SW::switch)
+                    .map(|switch| build_route_from_switch::<SW, T>(switch).route);
+                matched.is_some() && matched == target.link
+            }
+        }
+    }
+
+    /// The classes of the component, taking into account if it is active.
+    pub(crate) fn classes(&self, active: bool) -> String {
+        if active && !self.active_class.is_empty() {
+            format!("{} {}", self.classes, self.active_class)
+        } else {
+            self.classes.clone()
+        }
+    }
+}
+
+/// Subscribes to route changes if the component needs to know when it is active.
+///
+/// This should only be called once the component is mounted.
+fn active_route_bridge<T, SW, COMP>(
+    props: &Props<T, SW, COMP>,
+    link: &mut ComponentLink<COMP>,
+) -> Option<RouteAgentBridge<T>>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    COMP: Component<Message = Msg>,
+{
+    if props.active_class.is_empty() {
+        return None;
+    }
+    let callback = link.send_back(|route: Route<T>| Msg::RouteChanged(route.route));
+    let mut bridge = RouteAgentBridge::new(callback);
+    bridge.send(RouteRequest::GetCurrentRoute);
+    Some(bridge)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yew::html;
+
+    fn props(link: &str, active_match: ActiveMatch) -> Props<(), String, RouterLink<(), String>> {
+        Props {
+            link: Some(link.to_string()),
+            classes: "link".to_string(),
+            active_class: "active".to_string(),
+            active_match,
+            ..Props::default()
+        }
+    }
+
+    #[test]
+    fn exact_match_requires_equal_routes() {
+        let mut props = props("/users", ActiveMatch::Exact);
+        let target = props.take_target();
+        assert!(props.is_active(&target, "/users"));
+        assert!(!props.is_active(&target, "/users/12"));
+        assert!(!props.is_active(&target, "/users?page=2"));
+    }
+
+    #[test]
+    fn prefix_match_ends_at_section_boundaries() {
+        let mut props = props("/users", ActiveMatch::Prefix);
+        let target = props.take_target();
+        assert!(props.is_active(&target, "/users"));
+        assert!(props.is_active(&target, "/users/12"));
+        assert!(props.is_active(&target, "/users?page=2"));
+        assert!(!props.is_active(&target, "/usersettings"));
+    }
+
+    #[test]
+    fn switch_match_compares_switches_within_scope() {
+        let mut props = props("/users", ActiveMatch::Switch);
+        props.scope = RouteScope::new("/app");
+        let target = props.take_target();
+        assert_eq!(target.route.route, "/app/users");
+        assert!(props.is_active(&target, "/app/users"));
+        assert!(!props.is_active(&target, "/users"));
+        assert!(!props.is_active(&target, "/app/posts"));
+    }
+
+    #[test]
+    fn relative_links_are_resolved_against_current_route() {
+        let mut props = props("?page=2", ActiveMatch::Exact);
+        let target = props.take_target();
+        assert!(props.is_active(&target, "/users?page=2"));
+        assert!(!props.is_active(&target, "/users?page=3"));
+    }
+
+    #[test]
+    fn missing_link_is_the_empty_route() {
+        let mut props: Props<(), String, RouterLink<(), String>> = Props::default();
+        let target = props.take_target();
+        assert_eq!(target.route.route, "");
+        assert!(props.is_active(&target, "/users"));
+        props.active_match = ActiveMatch::Switch;
+        assert!(!props.is_active(&target, "/users"));
+    }
+
+    #[test]
+    fn replace_mode_replaces_route() {
+        let mut props = props("/users", ActiveMatch::Exact);
+        let target = props.take_target();
+        match props.request(&target) {
+            RouteRequest::ChangeRoute(route) => assert_eq!(route.route, "/users"),
+            request => panic!("expected a route change, got {:?}", request),
+        }
+        props.replace = true;
+        match props.request(&target) {
+            RouteRequest::ReplaceRoute(route) => assert_eq!(route.route, "/users"),
+            request => panic!("expected a route replacement, got {:?}", request),
+        }
+    }
+
+    #[test]
+    fn optional_attributes_are_set() {
+        let mut props = props("/users", ActiveMatch::Exact);
+        props.id = "users".to_string();
+        props.attributes = vec![("data-test".to_string(), "link".to_string())];
+        let mut html: Html<RouterLink<(), String>> = html! { <a></a> };
+        props.set_attributes(&mut html, false);
+        match html {
+            VNode::VTag(tag) => {
+                let attribute = |name: &str| tag.attributes.get(name).map(String::as_str);
+                assert_eq!(attribute("id"), Some("users"));
+                assert_eq!(attribute("title"), None);
+                assert_eq!(attribute("data-test"), Some("link"));
+                assert_eq!(attribute("aria-current"), None);
+            }
+            _ => panic!("expected a tag"),
+        }
+    }
+
+    #[test]
+    fn active_components_are_marked_as_current() {
+        let props = props("/users", ActiveMatch::Exact);
+        let mut html: Html<RouterLink<(), String>> = html! { <a></a> };
+        props.set_attributes(&mut html, true);
+        match html {
+            VNode::VTag(tag) => assert_eq!(
+                tag.attributes.get("aria-current").map(String::as_str),
+                Some("page")
+            ),
+            _ => panic!("expected a tag"),
+        }
+    }
+
+    #[test]
+    fn active_class_is_appended() {
+        let props = props("/users", ActiveMatch::Exact);
+        assert_eq!(props.classes(true), "link active");
+        assert_eq!(props.classes(false), "link");
+    }
 }
@@ -0,0 +1,138 @@
+//! A component that replaces the current route when rendered.
+use crate::agent::{RouteAgentDispatcher, RouteRequest};
+use std::{
+    fmt::{Debug, Error as FmtError, Formatter},
+    marker::PhantomData,
+};
+use yew::prelude::*;
+
+use crate::{
+    route::{Route, RouteScope},
+    switch::build_route_from_switch,
+    RouterState, Switch,
+};
+
+/// Properties for `Redirect`.
+#[derive(Properties, Debug)]
+pub struct RedirectProps<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    /// The route to redirect to.
+    #[props(required)]
+    pub to: SW,
+    /// The state to set along with the route.
+    /// If not provided, the state built from the switch is used.
+    pub state: Option<T>,
+    /// The route is relative to this scope.
+    pub scope: RouteScope,
+}
+
+impl<T, SW> RedirectProps<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    /// Builds the route to redirect to.
+    fn into_route(self) -> Route<T> {
+        let route: Route<T> = build_route_from_switch(self.to);
+        Route {
+            route: self.scope.join(&route.route),
+            state: self.state.or(route.state),
+        }
+    }
+}
+
+/// A component that replaces the current route with the provided one as soon as it is rendered.
+///
+/// Because the route is replaced instead of pushed, the redirecting route will not show up in the
+/// browser's history.
+///
+/// # Example
+/// ```
+/// # use yew::{html, Html};
+/// # use yew_router::{components::Redirect, router::Router};
+/// # pub enum Msg {}
+/// # fn dont_execute() {
+/// let render = Router::render(|switch: Option<String>| -> Html<Router<(), String, Msg>> {
+///     match switch {
+///         Some(_) => html! {"Home"},
+///         None => html! { <Redirect<(), String> to=String::from("/") /> },
+///     }
+/// });
+/// # }
+/// ```
+pub struct Redirect<T, SW = String>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    router: RouteAgentDispatcher<T>,
+    /// The route built from the properties.
+    route: Route<T>,
+    switch: PhantomData<SW>,
+}
+
+impl<T, SW> Debug for Redirect<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("Redirect")
+            .field("router", &self.router)
+            .field("route", &self.route)
+            .finish()
+    }
+}
+
+impl<T, SW> Component for Redirect<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    type Message = ();
+    type Properties = RedirectProps<T, SW>;
+
+    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+        Redirect {
+            router: RouteAgentDispatcher::new(),
+            route: props.into_route(),
+            switch: PhantomData,
+        }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        self.redirect();
+        false
+    }
+
+    fn update(&mut self, _msg: Self::Message) -> ShouldRender {
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let route = props.into_route();
+        if self.route != route {
+            self.route = route;
+            self.redirect();
+        }
+        false
+    }
+
+    fn view(&self) -> Html<Self> {
+        html! {}
+    }
+}
+
+impl<T, SW> Redirect<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    fn redirect(&mut self) {
+        self.router
+            .send(RouteRequest::ReplaceRoute(self.route.clone()));
+    }
+}
@@ -1,56 +1,123 @@
 //! A component wrapping a `<button>` tag that changes the route.
-use crate::{
-    agent::{RouteAgentDispatcher, RouteRequest},
-    route::Route,
-};
+use crate::agent::{RouteAgentBridge, RouteAgentDispatcher};
+use std::fmt::{Debug, Error as FmtError, Formatter};
 use yew::prelude::*;
 
-use super::{Msg, Props};
-use crate::RouterState;
+use super::{active_route_bridge, Msg, Props, Target};
+use crate::{RouterState, Switch};
 
 /// Changes the route when clicked.
-#[derive(Debug)]
-pub struct RouterButton<T: for<'de> RouterState<'de>> {
+pub struct RouterButton<T, SW = String>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    link: ComponentLink<RouterButton<T, SW>>,
     router: RouteAgentDispatcher<T>,
-    props: Props<T>,
+    /// Only present if the component needs to know when it is active.
+    active_bridge: Option<RouteAgentBridge<T>>,
+    current_route: Option<String>,
+    props: Props<T, SW, RouterButton<T, SW>>,
+    /// The route built from the link of the properties.
+    target: Target<T>,
 }
 
-impl<T: for<'de> RouterState<'de>> Component for RouterButton<T> {
+impl<T, SW> Debug for RouterButton<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + Debug + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("RouterButton")
+            .field("router", &self.router)
+            .field("active_bridge", &self.active_bridge)
+            .field("current_route", &self.current_route)
+            .field("props", &self.props)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl<T, SW> Component for RouterButton<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
     type Message = Msg;
-    type Properties = Props<T>;
+    type Properties = Props<T, SW, Self>;
 
-    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+    fn create(mut props: Self::Properties, link: ComponentLink<Self>) -> Self {
         let router = RouteAgentDispatcher::new();
-        RouterButton { router, props }
+        let target = props.take_target();
+        RouterButton {
+            link,
+            router,
+            active_bridge: None,
+            current_route: None,
+            props,
+            target,
+        }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        self.active_bridge = active_route_bridge(&self.props, &mut self.link);
+        false
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Clicked => {
-                let route = Route {
-                    route: self.props.link.clone(),
-                    state: self.props.state.clone(),
-                };
-                self.router.send(RouteRequest::ChangeRoute(route));
+                self.router.send(self.props.request(&self.target));
                 false
             }
+            Msg::Ignored => false,
+            Msg::RouteChanged(route) => {
+                let was_active = self.is_active();
+                self.current_route = Some(route);
+                was_active != self.is_active()
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         self.props = props;
+        self.target = self.props.take_target();
+        if self.props.active_class.is_empty() {
+            self.active_bridge = None;
+            self.current_route = None;
+        } else if self.active_bridge.is_none() {
+            self.active_bridge = active_route_bridge(&self.props, &mut self.link);
+        }
         true
     }
     
-    fn view(&self) -> Html<RouterButton<T>> {
-        html! {
+    fn view(&self) -> Html<Self> {
+        let active = self.is_active();
+
+        let mut html = html! {
             <button
-                class=self.props.classes.clone(),
+                class=self.props.classes(active),
                 onclick=|_| Msg::Clicked,
                 disabled=self.props.disabled,
             >
                 {&self.props.text}
+                { self.props.children.iter().collect::<Html<Self>>() }
             </button>
-        }
+        };
+        self.props.set_attributes(&mut html, active);
+        html
+    }
+}
+
+impl<T, SW> RouterButton<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    fn is_active(&self) -> bool {
+        self.current_route
+            .as_ref()
+            .map(|route| self.props.is_active(&self.target, route))
+            .unwrap_or(false)
     }
 }
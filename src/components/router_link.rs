@@ -1,63 +1,203 @@
 //! A component wrapping an `<a>` tag that changes the route.
-use crate::{
-    agent::{RouteAgentDispatcher, RouteRequest},
-    route::Route,
-};
+use crate::agent::{RouteAgentBridge, RouteAgentDispatcher};
+use std::fmt::{Debug, Error as FmtError, Formatter};
 use yew::prelude::*;
 
-use super::{Msg, Props};
-use crate::RouterState;
+use super::{active_route_bridge, Msg, Props, Target};
+use crate::{RouterState, Switch};
 
 /// An anchor tag Component that when clicked, will navigate to the provided route.
-#[derive(Debug)]
-pub struct RouterLink<T: for<'de> RouterState<'de>> {
+///
+/// Only unmodified clicks with the primary mouse button are handled by the router, so
+/// Ctrl/Cmd-click or middle-click still open the route in a new tab.
+pub struct RouterLink<T, SW = String>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    link: ComponentLink<RouterLink<T, SW>>,
     router: RouteAgentDispatcher<T>,
-    props: Props<T>,
+    /// Only present if the component needs to know when it is active.
+    active_bridge: Option<RouteAgentBridge<T>>,
+    current_route: Option<String>,
+    props: Props<T, SW, RouterLink<T, SW>>,
+    /// The route built from the link of the properties.
+    target: Target<T>,
 }
 
-impl<T: for<'de> RouterState<'de>> Component for RouterLink<T> {
+impl<T, SW> Debug for RouterLink<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + Debug + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("RouterLink")
+            .field("router", &self.router)
+            .field("active_bridge", &self.active_bridge)
+            .field("current_route", &self.current_route)
+            .field("props", &self.props)
+            .field("target", &self.target)
+            .finish()
+    }
+}
+
+impl<T, SW> Component for RouterLink<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
     type Message = Msg;
-    type Properties = Props<T>;
+    type Properties = Props<T, SW, Self>;
 
-    fn create(props: Self::Properties, _link: ComponentLink<Self>) -> Self {
+    fn create(mut props: Self::Properties, link: ComponentLink<Self>) -> Self {
         let router = RouteAgentDispatcher::new();
-        RouterLink { router, props }
+        let target = props.take_target();
+        RouterLink {
+            link,
+            router,
+            active_bridge: None,
+            current_route: None,
+            props,
+            target,
+        }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        self.active_bridge = active_route_bridge(&self.props, &mut self.link);
+        false
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
             Msg::Clicked => {
-                let route = Route {
-                    route: self.props.link.clone(),
-                    state: self.props.state.clone(),
-                };
-                self.router.send(RouteRequest::ChangeRoute(route));
+                self.router.send(self.props.request(&self.target));
                 false
             }
+            Msg::Ignored => false,
+            Msg::RouteChanged(route) => {
+                let was_active = self.is_active();
+                self.current_route = Some(route);
+                was_active != self.is_active()
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
         self.props = props;
+        self.target = self.props.take_target();
+        if self.props.active_class.is_empty() {
+            self.active_bridge = None;
+            self.current_route = None;
+        } else if self.active_bridge.is_none() {
+            self.active_bridge = active_route_bridge(&self.props, &mut self.link);
+        }
         true
     }
 
     fn view(&self) -> Html<Self> {
-        use stdweb::web::event::IEvent;
-        let target: &str = &self.props.link;
+        use stdweb::web::event::{IEvent, IMouseEvent, MouseButton};
+        let href: String = self.target.route.route.clone();
+        let disabled = self.props.disabled;
+        let handles_target = handles_target(&self.props.target);
+        let active = self.is_active();
 
-        html! {
+        let mut html = html! {
             <a
-                class=self.props.classes.clone(),
-                onclick=|event | {
-                    event.prevent_default();
-                    Msg::Clicked
+                class=self.props.classes(active),
+                onclick=|event| {
+                    let modified = event.ctrl_key()
+                        || event.meta_key()
+                        || event.shift_key()
+                        || event.alt_key()
+                        || event.button() != MouseButton::Left;
+                    match classify_click(disabled, modified, handles_target) {
+                        Click::Navigate => {
+                            event.prevent_default();
+                            Msg::Clicked
+                        }
+                        Click::Suppress => {
+                            event.prevent_default();
+                            Msg::Ignored
+                        }
+                        Click::Browser => Msg::Ignored,
+                    }
                 },
-                disabled=self.props.disabled,
-                href=target,
+                disabled=disabled,
+                href=href,
+                target=self.props.target.clone(),
             >
                 {&self.props.text}
+                { self.props.children.iter().collect::<Html<Self>>() }
             </a>
-        }
+        };
+        self.props.set_attributes(&mut html, active);
+        html
+    }
+}
+
+impl<T, SW> RouterLink<T, SW>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+{
+    fn is_active(&self) -> bool {
+        self.current_route
+            .as_ref()
+            .map(|route| self.props.is_active(&self.target, route))
+            .unwrap_or(false)
+    }
+}
+
+/// How a click on a `RouterLink` is handled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Click {
+    /// The router navigates to the link.
+    Navigate,
+    /// The browser handles the click, for example by opening the link in a new tab.
+    Browser,
+    /// Nothing happens, because the link is disabled.
+    Suppress,
+}
+
+/// Checks if the router navigates to links with the target, other targets are left to the
+/// browser.
+fn handles_target(target: &str) -> bool {
+    target.is_empty() || target == "_self"
+}
+
+/// Determines how a click is handled, given whether it used a modifier key or a button other than
+/// the primary one.
+fn classify_click(disabled: bool, modified: bool, handles_target: bool) -> Click {
+    if disabled {
+        Click::Suppress
+    } else if modified || !handles_target {
+        Click::Browser
+    } else {
+        Click::Navigate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_clicks_navigate() {
+        assert_eq!(classify_click(false, false, true), Click::Navigate);
+    }
+
+    #[test]
+    fn modified_clicks_and_other_targets_are_left_to_browser() {
+        assert_eq!(classify_click(false, true, true), Click::Browser);
+        assert_eq!(classify_click(false, false, false), Click::Browser);
+        assert!(handles_target(""));
+        assert!(handles_target("_self"));
+        assert!(!handles_target("_blank"));
+    }
+
+    #[test]
+    fn disabled_links_suppress_clicks() {
+        assert_eq!(classify_click(true, false, true), Click::Suppress);
+        assert_eq!(classify_click(true, true, false), Click::Suppress);
     }
 }
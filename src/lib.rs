@@ -43,6 +43,8 @@
 //!   "agent") will be included.
 //! * "agent" - If enabled, the RouteAgent and its associated types will be included.
 //! * "components" - If enabled, the accessory components will be made available.
+//! * "lazy" - If enabled, the LazyRoute component for route targets that are resolved by a
+//!   `Future` will be included. This enables the futures support of stdweb.
 //! * "matchers" - If enabled, the full matcher suite will be available.
 //! * "regex_matcher" - If enabled, the regex matcher will be available. This can be disabled to
 //!   avoid including the Regex package.
@@ -0,0 +1,510 @@
+//! Wrapper around route url string, and associated history state.
+use crate::service::{RouteService, StateError};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+// use std::ops::Deref;
+use std::ops::Deref;
+
+mod query;
+mod resolve;
+
+pub use query::{Query, QueryChange};
+
+/// Any state that can be stored by the History API must meet the criteria of this trait.
+///
+/// The state is stored as JSON, see [StateError](../service/enum.StateError.html).
+pub trait RouteState: Clone + Default + Serialize + DeserializeOwned + 'static {}
+impl<T> RouteState for T where T: Clone + Default + Serialize + DeserializeOwned + 'static {}
+
+/// The representation of a route, segmented into different sections for easy access.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Route<T> {
+    /// The route string
+    pub route: String,
+    /// The state
+    pub state: Option<T>,
+}
+
+/// Formats a path, query, and fragment into a string.
+///
+/// # Note
+/// This expects that all three already have their expected separators (?, #, etc)
+pub(crate) fn format_route_string(path: &str, query: &str, fragment: &str) -> String {
+    format!(
+        "{path}{query}{fragment}",
+        path = path,
+        query = query,
+        fragment = fragment
+    )
+}
+
+/// Splits a route into its path, query and fragment.
+///
+/// The query and fragment don't include their `?` and `#` separators.
+pub(crate) fn split_route(route: &str) -> (&str, Option<&str>, Option<&str>) {
+    let (rest, fragment) = match route.find('#') {
+        Some(index) => (&route[..index], Some(&route[index + 1..])),
+        None => (route, None),
+    };
+    let (path, query) = match rest.find('?') {
+        Some(index) => (&rest[..index], Some(&rest[index + 1..])),
+        None => (rest, None),
+    };
+    (path, query, fragment)
+}
+
+/// Checks if the route starts with the prefix, and the prefix ends at a section boundary, so
+/// `/ab` doesn't start with `/a`.
+///
+/// A trailing `/` of the prefix is ignored, and an empty prefix matches every route.
+pub(crate) fn has_prefix(route: &str, prefix: &str) -> bool {
+    let prefix = prefix.trim_end_matches('/');
+    if !route.starts_with(prefix) {
+        return false;
+    }
+    match route[prefix.len()..].chars().next() {
+        None | Some('/') | Some('?') | Some('#') => true,
+        Some(_) => false,
+    }
+}
+
+impl<T: RouteState> Route<T> {
+    /// Gets the current route and its associated history state from the route service.
+    pub fn current_route(route_service: &RouteService<T>) -> Self {
+        let route = route_service.get_route();
+        let state = route_service.get_state();
+        Route { route, state }
+    }
+
+    /// Like `current_route`, but fails if the state of the history entry can't be decoded.
+    pub fn try_current_route(route_service: &RouteService<T>) -> Result<Self, StateError> {
+        let route = route_service.get_route();
+        let state = route_service.try_get_state()?;
+        Ok(Route { route, state })
+    }
+}
+
+/// Determines which differences are ignored when comparing routes using
+/// [eq_with](struct.Route.html#method.eq_with).
+///
+/// The default comparison ignores nothing, which is the same as comparing the route strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RouteComparison {
+    /// Routes that only differ in their fragment are equal.
+    pub ignore_fragment: bool,
+    /// Routes whose queries only differ in the order of their keys are equal.
+    /// The order of multiple values of the same key still matters.
+    pub ignore_query_order: bool,
+}
+
+impl<T> Route<T> {
+    /// Returns a string representation of the route.
+    pub fn to_string(&self) -> String {
+        self.route.to_string()
+    }
+
+    /// Creates a route from its path, query and fragment, without their `?` and `#` separators.
+    ///
+    /// This is the inverse of `path`, `query_string` and `fragment`.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::Route;
+    /// let route = Route::<()>::from("/a/b?c=d#e");
+    /// let parts = Route::<()>::from_parts(route.path(), route.query_string(), route.fragment());
+    /// assert_eq!(parts, route);
+    /// ```
+    pub fn from_parts(path: &str, query: Option<&str>, fragment: Option<&str>) -> Self {
+        let mut route = path.to_string();
+        if let Some(query) = query {
+            route.push('?');
+            route.push_str(query);
+        }
+        if let Some(fragment) = fragment {
+            route.push('#');
+            route.push_str(fragment);
+        }
+        Route { route, state: None }
+    }
+
+    /// Creates a route from its path segments, query and fragment.
+    ///
+    /// An empty query is left out of the route.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::{Query, Route};
+    /// let mut query = Query::new();
+    /// query.set("page", "2");
+    /// let route = Route::<()>::from_segments(&["users", "12"], &query, Some("top"));
+    /// assert_eq!(route.route, "/users/12?page=2#top");
+    /// ```
+    pub fn from_segments(segments: &[&str], query: &Query, fragment: Option<&str>) -> Self {
+        let mut path = String::new();
+        for segment in segments {
+            path.push('/');
+            path.push_str(segment);
+        }
+        let query = query.to_string();
+        let query = if query.is_empty() {
+            None
+        } else {
+            Some(query.as_str())
+        };
+        Self::from_parts(&path, query, fragment)
+    }
+
+    /// The path of the route, without its query or fragment.
+    pub fn path(&self) -> &str {
+        split_route(&self.route).0
+    }
+
+    /// The segments of the path.
+    ///
+    /// A trailing `/` results in an empty last segment, so `/a/` has the segments `a` and ``.
+    pub fn path_segments(&self) -> Vec<&str> {
+        let path = self.path();
+        if path.is_empty() {
+            return vec![];
+        }
+        path.trim_start_matches('/').split('/').collect()
+    }
+
+    /// The query of the route without its `?`, if it has one.
+    pub fn query_string(&self) -> Option<&str> {
+        split_route(&self.route).1
+    }
+
+    /// The fragment of the route without its `#`, if it has one.
+    pub fn fragment(&self) -> Option<&str> {
+        split_route(&self.route).2
+    }
+
+    /// Compares the routes, ignoring the differences allowed by the comparison.
+    /// The state is not compared.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::{Route, RouteComparison};
+    /// let a = Route::<()>::from("/list?a=1&b=2#top");
+    /// let b = Route::<()>::from("/list?b=2&a=1");
+    /// let comparison = RouteComparison {
+    ///     ignore_fragment: true,
+    ///     ignore_query_order: true,
+    /// };
+    /// assert!(a.eq_with(&b, comparison));
+    /// assert!(!a.eq_with(&b, RouteComparison::default()));
+    /// ```
+    pub fn eq_with<U>(&self, other: &Route<U>, comparison: RouteComparison) -> bool {
+        if self.path() != other.path() {
+            return false;
+        }
+        if !comparison.ignore_fragment && self.fragment() != other.fragment() {
+            return false;
+        }
+        if comparison.ignore_query_order {
+            fn sorted(query: Query) -> Vec<(String, String)> {
+                let mut pairs: Vec<(String, String)> = query
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect();
+                // The sort is stable, so multiple values of a key keep their order.
+                pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                pairs
+            }
+            sorted(self.query()) == sorted(other.query())
+        } else {
+            self.query_string() == other.query_string()
+        }
+    }
+
+    /// Resolves a reference against this route, following the reference resolution rules of
+    /// [RFC 3986](https://tools.ietf.org/html/rfc3986#section-5.2).
+    ///
+    /// The reference can be an absolute path, a path relative to this route (`child`, `./child`,
+    /// `../sibling`), or only consist of a query (`?page=2`) or a fragment (`#section`).
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::Route;
+    /// let route = Route::<()>::from("/users/12/posts?page=1");
+    /// assert_eq!(route.resolve("../settings"), "/users/settings");
+    /// assert_eq!(route.resolve("./comments"), "/users/12/comments");
+    /// assert_eq!(route.resolve("?page=2"), "/users/12/posts?page=2");
+    /// assert_eq!(route.resolve("#top"), "/users/12/posts?page=1#top");
+    /// ```
+    pub fn resolve(&self, reference: &str) -> String {
+        resolve::resolve(&self.route, reference)
+    }
+
+    /// Parses the query of the route.
+    pub fn query(&self) -> Query {
+        Query::parse(self.query_string().unwrap_or_default())
+    }
+
+    /// Replaces the query of the route, keeping its path and fragment.
+    ///
+    /// An empty query removes the `?` from the route.
+    pub fn set_query(&mut self, query: &Query) {
+        let (path, _, fragment) = split_route(&self.route);
+        let mut route = path.to_string();
+        if !query.is_empty() {
+            route.push('?');
+            route.push_str(&query.to_string());
+        }
+        if let Some(fragment) = fragment {
+            route.push('#');
+            route.push_str(fragment);
+        }
+        self.route = route;
+    }
+
+    /// Gets the first value of the query key.
+    pub fn query_param(&self, key: &str) -> Option<String> {
+        self.query().get(key).map(ToString::to_string)
+    }
+
+    /// Sets the value of the query key, replacing every existing value.
+    pub fn set_query_param(&mut self, key: &str, value: &str) {
+        self.update_query(&[QueryChange::Set(key.to_string(), value.to_string())]);
+    }
+
+    /// Removes every value of the query key.
+    pub fn remove_query_param(&mut self, key: &str) {
+        self.update_query(&[QueryChange::Remove(key.to_string())]);
+    }
+
+    /// Applies the changes to the query of the route, leaving other keys untouched.
+    ///
+    /// # Example
+    /// ```
+    /// use yew_router::route::{QueryChange, Route};
+    /// let mut route = Route::<()>::from("/list?filter=new&page=3#results");
+    /// route.update_query(&[
+    ///     QueryChange::Set("page".to_string(), "1".to_string()),
+    ///     QueryChange::Append("tag".to_string(), "rust".to_string()),
+    /// ]);
+    /// assert_eq!(route.route, "/list?filter=new&page=1&tag=rust#results");
+    /// ```
+    pub fn update_query(&mut self, changes: &[QueryChange]) {
+        let mut query = self.query();
+        for change in changes {
+            change.apply(&mut query);
+        }
+        self.set_query(&query);
+    }
+
+    /// Resolves another route against this one, keeping the other route's state.
+    pub fn resolve_route(&self, reference: Route<T>) -> Route<T> {
+        Route {
+            route: self.resolve(&reference.route),
+            state: reference.state,
+        }
+    }
+}
+
+impl<T> From<String> for Route<T> {
+    fn from(string: String) -> Route<T> {
+        Route {
+            route: string,
+            state: None,
+        }
+    }
+}
+
+impl<T> From<&str> for Route<T> {
+    fn from(string: &str) -> Route<T> {
+        Route {
+            route: string.to_string(),
+            state: None,
+        }
+    }
+}
+
+impl<T> Deref for Route<T> {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.route
+    }
+}
+
+/// The part of a route that a nested `Router` or link operates within.
+///
+/// A component rendered by a parent router for a route like `/a/{*:rest}` can be handed a scope of
+/// `/a`, which lets its own `Router` match against only the remainder of the route, and lets its
+/// links navigate relative to `/a`, without the component knowing where it was mounted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RouteScope {
+    prefix: String,
+}
+
+impl RouteScope {
+    /// Creates a scope covering every route.
+    pub fn root() -> Self {
+        RouteScope::default()
+    }
+
+    /// Creates a scope covering the routes that start with the prefix.
+    ///
+    /// The prefix should start with a `/`. A trailing `/` is ignored.
+    pub fn new(prefix: &str) -> Self {
+        RouteScope {
+            prefix: prefix.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// The prefix that routes within this scope start with.
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Creates a scope nested within this one.
+    pub fn nest(&self, prefix: &str) -> Self {
+        RouteScope::new(&self.join(prefix))
+    }
+
+    /// Turns a route relative to this scope into a full route.
+    ///
+    /// Relative references like `../sibling` or `?page=2` are relative to the current route
+    /// rather than the scope, so they are returned unchanged.
+    pub fn join(&self, route: &str) -> String {
+        if route.is_empty() || route.starts_with('/') {
+            format!("{}{}", self.prefix, route)
+        } else {
+            route.to_string()
+        }
+    }
+
+    /// Removes the scope's prefix from the route.
+    ///
+    /// Returns `None` if the route is not within this scope.
+    pub fn strip<T>(&self, route: Route<T>) -> Option<Route<T>> {
+        if !self.contains(&route.route) {
+            return None;
+        }
+        Some(Route {
+            route: route.route[self.prefix.len()..].to_string(),
+            state: route.state,
+        })
+    }
+
+    /// Checks if the route is within this scope.
+    pub fn contains(&self, route: &str) -> bool {
+        has_prefix(route, &self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn root_scope_does_not_change_routes() {
+        let scope = RouteScope::root();
+        assert_eq!(scope.join("/a/b"), "/a/b");
+        assert_eq!(
+            scope.strip(Route::<()>::from("/a/b")),
+            Some(Route::from("/a/b"))
+        );
+    }
+
+    #[test]
+    fn strip_removes_prefix() {
+        let scope = RouteScope::new("/a/");
+        assert_eq!(
+            scope.strip(Route::<()>::from("/a/b")),
+            Some(Route::from("/b"))
+        );
+        assert_eq!(scope.strip(Route::<()>::from("/a")), Some(Route::from("")));
+        assert_eq!(
+            scope.strip(Route::<()>::from("/a?q=1")),
+            Some(Route::from("?q=1"))
+        );
+    }
+
+    #[test]
+    fn strip_rejects_routes_outside_scope() {
+        let scope = RouteScope::new("/a");
+        assert_eq!(scope.strip(Route::<()>::from("/ab")), None);
+        assert_eq!(scope.strip(Route::<()>::from("/b/a")), None);
+    }
+
+    #[test]
+    fn contains_routes_at_section_boundaries() {
+        let scope = RouteScope::new("/a");
+        assert!(scope.contains("/a"));
+        assert!(scope.contains("/a/b"));
+        assert!(scope.contains("/a#b"));
+        assert!(!scope.contains("/ab"));
+        assert!(RouteScope::root().contains("/anything"));
+    }
+
+    #[test]
+    fn prefixes_end_at_section_boundaries() {
+        assert!(has_prefix("/admin", "/admin"));
+        assert!(has_prefix("/admin/users", "/admin/"));
+        assert!(has_prefix("/admin?tab=1", "/admin"));
+        assert!(!has_prefix("/administrator", "/admin"));
+        assert!(has_prefix("/anything", ""));
+        assert!(has_prefix("/anything", "/"));
+    }
+
+    #[test]
+    fn nested_scopes_join_prefixes() {
+        let scope = RouteScope::new("/a").nest("/b");
+        assert_eq!(scope.prefix(), "/a/b");
+        assert_eq!(scope.join("/c"), "/a/b/c");
+    }
+
+    #[test]
+    fn splits_route_into_parts() {
+        let route = Route::<()>::from("/a/b/?c=d&e#f");
+        assert_eq!(route.path(), "/a/b/");
+        assert_eq!(route.path_segments(), vec!["a", "b", ""]);
+        assert_eq!(route.query_string(), Some("c=d&e"));
+        assert_eq!(route.fragment(), Some("f"));
+
+        let route = Route::<()>::from("/a");
+        assert_eq!(route.path_segments(), vec!["a"]);
+        assert_eq!(route.query_string(), None);
+        assert_eq!(route.fragment(), None);
+    }
+
+    #[test]
+    fn parts_round_trip() {
+        for route in &["/a/b?c=d#e", "/", "", "/a?", "/a#", "?q#f", "/a#b?c"] {
+            let route = Route::<()>::from(*route);
+            let parts = Route::from_parts(route.path(), route.query_string(), route.fragment());
+            assert_eq!(parts, route);
+        }
+    }
+
+    #[test]
+    fn segments_round_trip() {
+        for route in &["/a/b?c=d#e", "/", "/a/"] {
+            let route = Route::<()>::from(*route);
+            let segments =
+                Route::from_segments(&route.path_segments(), &route.query(), route.fragment());
+            assert_eq!(segments, route);
+        }
+    }
+
+    #[test]
+    fn compares_with_options() {
+        let ignore_fragment = RouteComparison {
+            ignore_fragment: true,
+            ..RouteComparison::default()
+        };
+        let ignore_query_order = RouteComparison {
+            ignore_query_order: true,
+            ..RouteComparison::default()
+        };
+        let a = Route::<()>::from("/a?x=1&y=2&x=3#f");
+        assert!(a.eq_with(&Route::<()>::from("/a?x=1&y=2&x=3"), ignore_fragment));
+        assert!(!a.eq_with(&Route::<()>::from("/a?y=2&x=1&x=3"), ignore_fragment));
+        assert!(a.eq_with(&Route::<()>::from("/a?y=2&x=1&x=3#f"), ignore_query_order));
+        assert!(!a.eq_with(&Route::<()>::from("/a?y=2&x=3&x=1#f"), ignore_query_order));
+        assert!(!a.eq_with(&Route::<()>::from("/b?x=1&y=2&x=3#f"), ignore_fragment));
+    }
+}
@@ -0,0 +1,322 @@
+//! Parsed query strings.
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    hash::{Hash, Hasher},
+};
+
+/// The key-value pairs of a route's query string, in the order they appear.
+///
+/// Keys may appear multiple times.
+/// Keys and values are percent-decoded when parsed.
+/// Pairs that weren't changed since they were parsed are written back out exactly as they were
+/// parsed, other pairs are percent-encoded.
+///
+/// # Example
+/// ```
+/// use yew_router::route::Query;
+/// let mut query = Query::parse("?tag=a&tag=b&page=2");
+/// assert_eq!(query.get("page"), Some("2"));
+/// assert_eq!(query.get_all("tag"), vec!["a", "b"]);
+/// query.set("page", "3");
+/// query.remove("tag");
+/// assert_eq!(query.to_string(), "page=3");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Query {
+    pairs: Vec<Pair>,
+}
+
+/// A key-value pair of a query.
+///
+/// Pairs are compared by their decoded key and value only.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Pair {
+    key: String,
+    value: String,
+    /// The text the pair was parsed from, if it hasn't been changed since.
+    raw: Option<String>,
+}
+
+impl Pair {
+    fn new(key: &str, value: &str) -> Self {
+        Pair {
+            key: key.to_string(),
+            value: value.to_string(),
+            raw: None,
+        }
+    }
+}
+
+impl PartialEq for Pair {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key && self.value == other.value
+    }
+}
+
+impl Eq for Pair {}
+
+impl Hash for Pair {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key.hash(state);
+        self.value.hash(state);
+    }
+}
+
+impl Query {
+    /// Creates an empty query.
+    pub fn new() -> Self {
+        Query::default()
+    }
+
+    /// Parses a query string, which may start with a `?`.
+    ///
+    /// Pairs without a `=` are given an empty value.
+    pub fn parse(query: &str) -> Self {
+        let query = query.trim_start_matches('?');
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut split = pair.splitn(2, '=');
+                let key = split.next().unwrap_or_default();
+                let value = split.next().unwrap_or_default();
+                Pair {
+                    key: decode(key),
+                    value: decode(value),
+                    raw: Some(pair.to_string()),
+                }
+            })
+            .collect();
+        Query { pairs }
+    }
+
+    /// Gets the first value of the key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|pair| pair.key == key)
+            .map(|pair| pair.value.as_str())
+    }
+
+    /// Gets every value of the key.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.pairs
+            .iter()
+            .filter(|pair| pair.key == key)
+            .map(|pair| pair.value.as_str())
+            .collect()
+    }
+
+    /// Checks if the key is present.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|pair| pair.key == key)
+    }
+
+    /// Sets the value of the key, replacing every existing value.
+    ///
+    /// The pair keeps the position of the first existing value, or is added to the end otherwise.
+    /// If the first existing value is unchanged, the pair is written back out as it was parsed.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self.pairs.iter().position(|pair| pair.key == key) {
+            Some(index) => {
+                if self.pairs[index].value != value {
+                    self.pairs[index] = Pair::new(key, value);
+                }
+                let mut position = 0;
+                self.pairs.retain(|pair| {
+                    let keep = pair.key != key || position == index;
+                    position += 1;
+                    keep
+                });
+            }
+            None => self.append(key, value),
+        }
+    }
+
+    /// Adds a value for the key, keeping any existing values.
+    pub fn append(&mut self, key: &str, value: &str) {
+        self.pairs.push(Pair::new(key, value));
+    }
+
+    /// Removes every value of the key.
+    pub fn remove(&mut self, key: &str) {
+        self.pairs.retain(|pair| pair.key != key);
+    }
+
+    /// Iterates over the key-value pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs
+            .iter()
+            .map(|pair| (pair.key.as_str(), pair.value.as_str()))
+    }
+
+    /// The number of key-value pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Checks if there are no key-value pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+/// Writes the pairs without a leading `?`.
+impl Display for Query {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (index, pair) in self.pairs.iter().enumerate() {
+            if index > 0 {
+                f.write_char('&')?;
+            }
+            match &pair.raw {
+                Some(raw) => f.write_str(raw)?,
+                None => write!(f, "{}={}", encode(&pair.key), encode(&pair.value))?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A change to apply to the query of the current route.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum QueryChange {
+    /// Sets the value of the key, replacing every existing value.
+    Set(String, String),
+    /// Adds a value for the key, keeping any existing values.
+    Append(String, String),
+    /// Removes every value of the key.
+    Remove(String),
+}
+
+impl QueryChange {
+    /// Applies the change to the query.
+    pub fn apply(&self, query: &mut Query) {
+        match self {
+            QueryChange::Set(key, value) => query.set(key, value),
+            QueryChange::Append(key, value) => query.append(key, value),
+            QueryChange::Remove(key) => query.remove(key),
+        }
+    }
+}
+
+/// Percent-decodes a query component, treating `+` as a space.
+///
+/// Invalid escapes are kept as they are.
+fn decode(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let escape = component
+                    .get(index + 1..index + 3)
+                    .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match escape {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        index += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Percent-encodes a query component.
+fn encode(component: &str) -> String {
+    let mut encoded = String::with_capacity(component.len());
+    for byte in component.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'\'' | b'(' | b')' | b'*' | b',' | b'/'
+            | b':' | b';' | b'@' | b'?' => encoded.push(byte as char),
+            byte => {
+                let _ = write!(encoded, "%{:02X}", byte);
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pairs_in_order() {
+        let query = Query::parse("?a=1&b=2&a=3&flag");
+        let pairs: Vec<(&str, &str)> = query.iter().collect();
+        assert_eq!(
+            pairs,
+            vec![("a", "1"), ("b", "2"), ("a", "3"), ("flag", "")]
+        );
+        assert_eq!(query.get("a"), Some("1"));
+        assert_eq!(query.get_all("a"), vec!["1", "3"]);
+        assert_eq!(query.get("missing"), None);
+    }
+
+    #[test]
+    fn parses_empty_query() {
+        assert!(Query::parse("").is_empty());
+        assert!(Query::parse("?").is_empty());
+    }
+
+    #[test]
+    fn set_replaces_every_value() {
+        let mut query = Query::parse("a=1&b=2&a=3");
+        query.set("a", "4");
+        assert_eq!(query.to_string(), "a=4&b=2");
+        query.set("c", "5");
+        assert_eq!(query.to_string(), "a=4&b=2&c=5");
+    }
+
+    #[test]
+    fn append_and_remove() {
+        let mut query = Query::parse("a=1");
+        query.append("a", "2");
+        assert_eq!(query.get_all("a"), vec!["1", "2"]);
+        query.remove("a");
+        assert!(query.is_empty());
+    }
+
+    #[test]
+    fn round_trips_encoded_components() {
+        let query = Query::parse("q=hello+world&tag=a%26b&empty=&flag");
+        assert_eq!(query.get("q"), Some("hello world"));
+        assert_eq!(query.get("tag"), Some("a&b"));
+        assert_eq!(query.to_string(), "q=hello+world&tag=a%26b&empty=&flag");
+        assert_eq!(Query::parse(&query.to_string()), query);
+    }
+
+    #[test]
+    fn encodes_changed_pairs_only() {
+        let mut query = Query::parse("flag&q=a+b&tag=a%26b");
+        query.set("q", "c d");
+        query.set("tag", "a&b");
+        query.append("next", "x&y");
+        assert_eq!(query.to_string(), "flag&q=c%20d&tag=a%26b&next=x%26y");
+    }
+
+    #[test]
+    fn compares_decoded_pairs() {
+        let mut query = Query::new();
+        query.set("q", "a b");
+        assert_eq!(Query::parse("q=a+b"), query);
+        assert_eq!(Query::parse("q=a%20b"), query);
+    }
+
+    #[test]
+    fn keeps_invalid_escapes() {
+        assert_eq!(Query::parse("a=%zz&b=%4").get("a"), Some("%zz"));
+        assert_eq!(Query::parse("a=%zz&b=%4").get("b"), Some("%4"));
+        assert_eq!(Query::parse("a=%+1").get("a"), Some("% 1"));
+    }
+}
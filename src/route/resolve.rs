@@ -0,0 +1,141 @@
+//! Resolution of relative route references, following
+//! [RFC 3986 section 5.2](https://tools.ietf.org/html/rfc3986#section-5.2).
+//!
+//! Routes don't have a scheme or authority, so references that do are left untouched.
+use super::split_route;
+
+/// Checks if the reference has a scheme (`mailto:`) or an authority (`//host`).
+fn has_scheme_or_authority(reference: &str) -> bool {
+    if reference.starts_with("//") {
+        return true;
+    }
+    let end = reference.find(&['/', '?', '#'][..]);
+    let first_section = &reference[..end.unwrap_or(reference.len())];
+    match first_section.find(':') {
+        Some(index) => {
+            let scheme = &first_section[..index];
+            scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+        }
+        None => false,
+    }
+}
+
+/// Merges a relative path with the path of the base route.
+fn merge(base_path: &str, reference_path: &str) -> String {
+    match base_path.rfind('/') {
+        Some(index) => format!("{}{}", &base_path[..=index], reference_path),
+        None => format!("/{}", reference_path),
+    }
+}
+
+/// Removes `.` and `..` segments from a path.
+fn remove_dot_segments(path: &str) -> String {
+    let mut input = path;
+    let mut output: Vec<&str> = vec![];
+    while !input.is_empty() {
+        if input.starts_with("../") {
+            input = &input[3..];
+        } else if input.starts_with("./") || input.starts_with("/./") {
+            input = &input[2..];
+        } else if input == "/." {
+            input = "/";
+        } else if input.starts_with("/../") {
+            input = &input[3..];
+            output.pop();
+        } else if input == "/.." {
+            input = "/";
+            output.pop();
+        } else if input == "." || input == ".." {
+            input = "";
+        } else {
+            // Move the first segment, including its leading `/`, to the output.
+            let start = if input.starts_with('/') { 1 } else { 0 };
+            let end = input[start..]
+                .find('/')
+                .map(|index| index + start)
+                .unwrap_or(input.len());
+            output.push(&input[..end]);
+            input = &input[end..];
+        }
+    }
+    output.concat()
+}
+
+/// Resolves a reference against a base route.
+///
+/// The reference can be an absolute path (`/a/b`), a relative path (`../a`, `./b`, `c`),
+/// or only consist of a query (`?page=2`) or a fragment (`#section`).
+pub(crate) fn resolve(base: &str, reference: &str) -> String {
+    if has_scheme_or_authority(reference) {
+        return reference.to_string();
+    }
+    let (base_path, base_query, _) = split_route(base);
+    let (reference_path, reference_query, fragment) = split_route(reference);
+
+    let (path, query) = if reference_path.is_empty() {
+        (base_path.to_string(), reference_query.or(base_query))
+    } else if reference_path.starts_with('/') {
+        (remove_dot_segments(reference_path), reference_query)
+    } else {
+        let merged = merge(base_path, reference_path);
+        (remove_dot_segments(&merged), reference_query)
+    };
+
+    let mut route = path;
+    if let Some(query) = query {
+        route.push('?');
+        route.push_str(query);
+    }
+    if let Some(fragment) = fragment {
+        route.push('#');
+        route.push_str(fragment);
+    }
+    route
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "/b/c/d;p?q";
+
+    #[test]
+    fn resolves_relative_paths() {
+        assert_eq!(resolve(BASE, "g"), "/b/c/g");
+        assert_eq!(resolve(BASE, "./g"), "/b/c/g");
+        assert_eq!(resolve(BASE, "g/"), "/b/c/g/");
+        assert_eq!(resolve(BASE, "."), "/b/c/");
+        assert_eq!(resolve(BASE, "./"), "/b/c/");
+        assert_eq!(resolve(BASE, ".."), "/b/");
+        assert_eq!(resolve(BASE, "../g"), "/b/g");
+        assert_eq!(resolve(BASE, "../.."), "/");
+        assert_eq!(resolve(BASE, "../../g"), "/g");
+        assert_eq!(resolve(BASE, "../../../g"), "/g");
+        assert_eq!(resolve(BASE, "g;x?y#s"), "/b/c/g;x?y#s");
+    }
+
+    #[test]
+    fn resolves_absolute_paths() {
+        assert_eq!(resolve(BASE, "/g"), "/g");
+        assert_eq!(resolve(BASE, "/./g"), "/g");
+        assert_eq!(resolve(BASE, "/../g"), "/g");
+        assert_eq!(resolve(BASE, "/a/b/../c"), "/a/c");
+    }
+
+    #[test]
+    fn resolves_query_and_fragment_only_references() {
+        assert_eq!(resolve(BASE, "?y"), "/b/c/d;p?y");
+        assert_eq!(resolve(BASE, "#s"), "/b/c/d;p?q#s");
+        assert_eq!(resolve(BASE, ""), "/b/c/d;p?q");
+        assert_eq!(resolve("/list?page=1#top", "?page=2"), "/list?page=2");
+    }
+
+    #[test]
+    fn leaves_other_references_alone() {
+        assert_eq!(resolve(BASE, "//host/g"), "//host/g");
+        assert_eq!(resolve(BASE, "mailto:someone"), "mailto:someone");
+    }
+}
@@ -0,0 +1,95 @@
+//! Everything a render function may need to know about the matched route.
+use crate::{
+    route::{Route, RouteScope},
+    router::LoadState,
+    switch::build_route_from_switch,
+    Switch,
+};
+
+/// The matched route, given to render functions created with `Router::render_context`.
+#[derive(Debug)]
+pub struct RouteContext<'a, T, SW, D = (), E = ()> {
+    /// The switch matched from the route, or `None` if no route was matched.
+    pub switch: Option<SW>,
+    /// The full route, including its query, fragment and state.
+    pub route: &'a Route<T>,
+    /// The scope of the router that matched the route.
+    pub scope: &'a RouteScope,
+    /// The state of the data loaded for the route.
+    pub load_state: &'a LoadState<D, E>,
+}
+
+impl<'a, T, SW: Switch + Clone, D, E> RouteContext<'a, T, SW, D, E> {
+    /// The part of the route that follows the part the switch was matched from.
+    ///
+    /// Switches that don't end in a `!` match routes that start with them, so the remainder can be
+    /// left for a nested router to match.
+    /// Returns `None` if no route was matched, or if the matched switch can't be written back out
+    /// as the start of the route.
+    pub fn remainder(&self) -> Option<&'a str> {
+        let matched = self.matched()?;
+        let start = self.scope.prefix().len() + matched.len();
+        self.route.route.get(start..)
+    }
+
+    /// The scope for a nested router that matches the `remainder` of the route.
+    pub fn child_scope(&self) -> Option<RouteScope> {
+        self.matched().map(|matched| self.scope.nest(&matched))
+    }
+
+    /// Writes the switch back out, if the route within the scope starts with it.
+    fn matched(&self) -> Option<String> {
+        let switch = self.switch.clone()?;
+        let matched: Route<()> = build_route_from_switch(switch);
+        let scoped = self.route.route.get(self.scope.prefix().len()..)?;
+        if scoped.starts_with(&matched.route) {
+            Some(matched.route)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(
+        switch: Option<&str>,
+        route: &'a Route<()>,
+        scope: &'a RouteScope,
+    ) -> RouteContext<'a, (), String> {
+        RouteContext {
+            switch: switch.map(String::from),
+            route,
+            scope,
+            load_state: &LoadState::Idle,
+        }
+    }
+
+    #[test]
+    fn remainder_follows_matched_switch() {
+        let route = Route::from("/users/42?tab=posts");
+        let scope = RouteScope::default();
+        let context = context(Some("/users"), &route, &scope);
+        assert_eq!(context.remainder(), Some("/42?tab=posts"));
+        assert_eq!(context.child_scope(), Some(RouteScope::new("/users")));
+    }
+
+    #[test]
+    fn remainder_is_relative_to_scope() {
+        let route = Route::from("/app/users/42");
+        let scope = RouteScope::new("/app");
+        let context = context(Some("/users"), &route, &scope);
+        assert_eq!(context.remainder(), Some("/42"));
+        assert_eq!(context.child_scope(), Some(RouteScope::new("/app/users")));
+    }
+
+    #[test]
+    fn no_remainder_without_match() {
+        let route = Route::from("/users/42");
+        let scope = RouteScope::default();
+        assert_eq!(context(None, &route, &scope).remainder(), None);
+        assert_eq!(context(Some("/posts"), &route, &scope).remainder(), None);
+    }
+}
@@ -0,0 +1,287 @@
+//! Route targets that become available asynchronously.
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Debug, Error as FmtError, Formatter},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+};
+use yew::{html, Callback, Component, ComponentLink, Html, Properties, ShouldRender};
+
+type Waiter = Callback<(String, LazyView)>;
+
+thread_local! {
+    static CACHE: RefCell<LazyCache<LazyView, Waiter>> = RefCell::new(LazyCache::new());
+}
+
+/// Views that have already been resolved, and the components waiting for views that are being
+/// resolved, by the key of their target.
+struct LazyCache<V, W> {
+    views: HashMap<String, V>,
+    waiting: HashMap<String, Vec<W>>,
+}
+
+impl<V: Clone, W> LazyCache<V, W> {
+    fn new() -> Self {
+        LazyCache {
+            views: HashMap::new(),
+            waiting: HashMap::new(),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<V> {
+        self.views.get(key).cloned()
+    }
+
+    /// Queues the waiter until the view is resolved.
+    ///
+    /// Returns `true` if nobody else is waiting for the view yet, in which case the caller has to
+    /// resolve it.
+    fn wait(&mut self, key: &str, waiter: W) -> bool {
+        let waiting = self.waiting.entry(key.to_string()).or_insert_with(Vec::new);
+        waiting.push(waiter);
+        waiting.len() == 1
+    }
+
+    /// Caches the resolved view, returning everyone that waited for it.
+    fn resolved(&mut self, key: &str, view: V) -> Vec<W> {
+        self.views.insert(key.to_string(), view);
+        self.waiting.remove(key).unwrap_or_default()
+    }
+}
+
+/// A view that renders a lazily loaded route target.
+pub struct LazyView(Rc<dyn Fn() -> Html<LazyRoute>>);
+
+impl LazyView {
+    /// Wraps a render function, usually one that renders a component, in a view.
+    pub fn new<F: Fn() -> Html<LazyRoute> + 'static>(f: F) -> Self {
+        LazyView(Rc::new(f))
+    }
+
+    fn render(&self) -> Html<LazyRoute> {
+        (&self.0)()
+    }
+}
+
+impl Clone for LazyView {
+    fn clone(&self) -> Self {
+        LazyView(Rc::clone(&self.0))
+    }
+}
+
+impl Debug for LazyView {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyView").finish()
+    }
+}
+
+type Resolver = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = LazyView>>>>;
+
+/// A route target whose view is produced by a `Future`.
+///
+/// The future is only created and polled the first time a target with the given key is rendered.
+/// Every other `LazyRoute` using the same key waits for it to resolve, and once it does, the view
+/// is cached and rendered right away by every `LazyRoute` using the same key.
+#[derive(Clone)]
+pub struct LazyTarget {
+    key: String,
+    resolver: Resolver,
+}
+
+impl LazyTarget {
+    /// Creates a target identified by the key, that is resolved by the future returned by the
+    /// resolver.
+    ///
+    /// # Example
+    /// ```
+    /// # use yew::html;
+    /// # use yew_router::router::{LazyTarget, LazyView};
+    /// let target = LazyTarget::new("settings", || {
+    ///     async {
+    ///         // Wait for a split module or other deferred initialization here.
+    ///         LazyView::new(|| html! {"Settings"})
+    ///     }
+    /// });
+    /// ```
+    pub fn new<F, FUT>(key: &str, resolver: F) -> Self
+    where
+        F: Fn() -> FUT + 'static,
+        FUT: Future<Output = LazyView> + 'static,
+    {
+        LazyTarget {
+            key: key.to_string(),
+            resolver: Rc::new(move || -> Pin<Box<dyn Future<Output = LazyView>>> {
+                Box::pin(resolver())
+            }),
+        }
+    }
+
+    /// The key the view is cached with.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    fn cached(&self) -> Option<LazyView> {
+        CACHE.with(|cache| cache.borrow().get(&self.key))
+    }
+}
+
+impl Debug for LazyTarget {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("LazyTarget")
+            .field("key", &self.key)
+            .finish()
+    }
+}
+
+/// Properties for `LazyRoute`.
+#[derive(Properties, Debug)]
+pub struct LazyProps {
+    /// The target to render.
+    #[props(required)]
+    pub target: LazyTarget,
+    /// Rendered until the target has been resolved.
+    /// Nothing is rendered if this isn't provided.
+    pub placeholder: Option<LazyView>,
+}
+
+/// Message for `LazyRoute`.
+#[derive(Debug)]
+pub enum LazyMsg {
+    /// The target with the key has been resolved.
+    Resolved(String, LazyView),
+}
+
+/// A component that renders a placeholder until its target has been resolved, and the target's
+/// view afterwards.
+///
+/// # Example
+/// ```
+/// # use yew::{html, Html};
+/// # use yew_router::router::{LazyRoute, LazyTarget, LazyView, Router};
+/// # pub enum Msg {}
+/// # fn dont_execute() {
+/// let render = Router::render(|switch: Option<String>| -> Html<Router<(), String, Msg>> {
+///     match switch.as_ref().map(String::as_str) {
+///         Some("/settings") => {
+///             let target = LazyTarget::new("settings", || {
+///                 async { LazyView::new(|| html! {"Settings"}) }
+///             });
+///             html! { <LazyRoute target=target /> }
+///         }
+///         _ => html! {"404"},
+///     }
+/// });
+/// # }
+/// ```
+pub struct LazyRoute {
+    props: LazyProps,
+    link: ComponentLink<LazyRoute>,
+    view: Option<LazyView>,
+}
+
+impl Debug for LazyRoute {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("LazyRoute")
+            .field("props", &self.props)
+            .field("view", &self.view)
+            .finish()
+    }
+}
+
+impl Component for LazyRoute {
+    type Message = LazyMsg;
+    type Properties = LazyProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let view = props.target.cached();
+        LazyRoute { props, link, view }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        self.resolve();
+        false
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            LazyMsg::Resolved(key, view) => {
+                if key != self.props.target.key {
+                    return false;
+                }
+                self.view = Some(view);
+                true
+            }
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        let target_changed = self.props.target.key != props.target.key;
+        self.props = props;
+        if target_changed {
+            self.view = self.props.target.cached();
+            self.resolve();
+        }
+        true
+    }
+
+    fn view(&self) -> Html<Self> {
+        match (&self.view, &self.props.placeholder) {
+            (Some(view), _) => view.render(),
+            (None, Some(placeholder)) => placeholder.render(),
+            (None, None) => html! {},
+        }
+    }
+}
+
+impl LazyRoute {
+    /// Waits for the target to be resolved, starting to resolve it if nobody else has, unless it
+    /// has already been resolved.
+    fn resolve(&mut self) {
+        if self.view.is_some() {
+            return;
+        }
+        let key = self.props.target.key.clone();
+        let callback = self
+            .link
+            .send_back(|(key, view): (String, LazyView)| LazyMsg::Resolved(key, view));
+        if !CACHE.with(|cache| cache.borrow_mut().wait(&key, callback)) {
+            return;
+        }
+        let future = (&self.props.target.resolver)();
+        stdweb::spawn_local(async move {
+            let view = future.await;
+            // The view is cached even if the waiting components have gone away in the meantime.
+            let waiting = CACHE.with(|cache| cache.borrow_mut().resolved(&key, view.clone()));
+            for callback in waiting {
+                callback.emit((key.clone(), view.clone()));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_first_waiter_resolves() {
+        let mut cache: LazyCache<&str, u32> = LazyCache::new();
+        assert!(cache.wait("settings", 1));
+        assert!(!cache.wait("settings", 2));
+        assert!(cache.wait("profile", 3));
+        assert_eq!(cache.get("settings"), None);
+    }
+
+    #[test]
+    fn resolving_releases_waiters_and_caches_view() {
+        let mut cache: LazyCache<&str, u32> = LazyCache::new();
+        cache.wait("settings", 1);
+        cache.wait("settings", 2);
+        assert_eq!(cache.resolved("settings", "view"), vec![1, 2]);
+        assert_eq!(cache.get("settings"), Some("view"));
+        assert!(cache.resolved("settings", "view").is_empty());
+    }
+}
@@ -0,0 +1,94 @@
+//! Loading data for the matched route.
+use crate::router::closure_id;
+use std::{
+    any::TypeId,
+    fmt::{self, Debug, Formatter},
+    rc::Rc,
+};
+use yew::{services::Task, Callback};
+
+/// The state of the data loaded for the current route by the `Router`'s loader.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LoadState<D, E> {
+    /// There is no loader, or no route was matched.
+    Idle,
+    /// The loader hasn't finished yet.
+    Loading,
+    /// The loader finished successfully.
+    Loaded(D),
+    /// The loader failed.
+    Failed(E),
+}
+
+impl<D, E> LoadState<D, E> {
+    /// Checks if the loader hasn't finished yet.
+    pub fn is_loading(&self) -> bool {
+        match self {
+            LoadState::Loading => true,
+            _ => false,
+        }
+    }
+}
+
+impl<D, E> Default for LoadState<D, E> {
+    fn default() -> Self {
+        LoadState::Idle
+    }
+}
+
+/// Loads data for a matched route.
+///
+/// The loader is given the matched route and a callback it should call with the result once it is
+/// available.
+/// It can return the task performing the load, like a `FetchTask`, which is dropped to cancel the
+/// load if the route changes before it finishes.
+pub trait LoaderFn<SW, D, E>: Fn(&SW, Callback<Result<D, E>>) -> Option<Box<dyn Task>> {}
+impl<T, SW, D, E> LoaderFn<SW, D, E> for T where
+    T: Fn(&SW, Callback<Result<D, E>>) -> Option<Box<dyn Task>>
+{
+}
+
+/// Owned loader function.
+pub struct Loader<SW, D, E>(pub(crate) Rc<dyn LoaderFn<SW, D, E>>, Option<TypeId>);
+
+impl<SW, D, E> Loader<SW, D, E> {
+    /// Wraps a loader function so that it can be used by the Router.
+    ///
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::Loader;
+    /// # #[derive(Switch)]
+    /// # enum S {
+    /// #     #[to = "/user/{id}"]
+    /// #     User(usize),
+    /// # }
+    /// let loader = Loader::new(|switch: &S, callback| {
+    ///     match switch {
+    ///         S::User(id) => callback.emit(Ok::<String, ()>(format!("User {}", id))),
+    ///     }
+    ///     None
+    /// });
+    /// ```
+    pub fn new<F: LoaderFn<SW, D, E> + 'static>(f: F) -> Self {
+        Loader(Rc::new(f), closure_id::<F>())
+    }
+}
+
+impl<SW, D, E> Clone for Loader<SW, D, E> {
+    fn clone(&self) -> Self {
+        Loader(Rc::clone(&self.0), self.1)
+    }
+}
+
+impl<SW, D, E> PartialEq for Loader<SW, D, E> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || (self.1.is_some() && self.1 == other.1)
+    }
+}
+
+impl<SW, D, E> Debug for Loader<SW, D, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Loader").finish()
+    }
+}
@@ -1,11 +1,56 @@
 //! Router component and related types.
+mod context;
+#[cfg(feature = "lazy")]
+mod lazy;
+mod loader;
 mod router;
+mod transition;
 
-pub use router::{Props, Render, Router};
+pub use context::RouteContext;
+#[cfg(feature = "lazy")]
+pub use lazy::{LazyMsg, LazyProps, LazyRoute, LazyTarget, LazyView};
+pub use loader::{LoadState, Loader, LoaderFn};
+pub use router::{
+    ContextRenderFn, LoadedRenderFn, Props, Render, RenderFn, RenderNotFound, Router,
+};
+pub use transition::{Transition, TransitionDirection};
 
 use crate::agent::AgentState;
+use std::any::TypeId;
 
 /// Any state that can be managed by the `Router` must meet the criteria of this trait.
 pub trait RouterState<'de>: AgentState<'de> + PartialEq {}
 
 impl<'de, T> RouterState<'de> for T where T: AgentState<'de> + PartialEq {}
+
+/// Identifies closures that don't capture anything.
+///
+/// Such closures behave the same whenever their types are the same, so functions that are
+/// recreated every time a parent renders can still be compared.
+pub(crate) fn closure_id<F: 'static>() -> Option<TypeId> {
+    if std::mem::size_of::<F>() == 0 {
+        Some(TypeId::of::<F>())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn id_of<F: Fn() + 'static>(_: &F) -> Option<TypeId> {
+        closure_id::<F>()
+    }
+
+    #[test]
+    fn identifies_closures_without_captures() {
+        let empty = || {};
+        assert!(id_of(&empty).is_some());
+        assert_eq!(id_of(&empty), id_of(&empty));
+        assert_ne!(id_of(&empty), id_of(&|| {}));
+
+        let captured = 1;
+        assert_eq!(id_of(&move || assert_eq!(captured, 1)), None);
+    }
+}
@@ -1,22 +1,44 @@
 //! Router Component.
 
 use crate::{
-    agent::{RouteAgentBridge, RouteRequest},
-    route::Route,
-    router::RouterState,
+    agent::{RouteAgentBridge, RouteChange, RouteRequest},
+    route::{Route, RouteComparison, RouteScope},
+    router::{
+        closure_id,
+        transition::{Slot, Transitions},
+        LoadState, Loader, RouteContext, RouterState, Transition, TransitionDirection,
+    },
     Switch,
 };
 use std::{
+    any::TypeId,
     fmt::{self, Debug, Error as FmtError, Formatter},
     rc::Rc,
 };
-use yew::{virtual_dom::VNode, Callback, Component, ComponentLink, Html, Properties, ShouldRender};
+use yew::{
+    html,
+    services::{timeout::TimeoutTask, Task, TimeoutService},
+    virtual_dom::VNode,
+    Callback, Component, ComponentLink, Html, Properties, ShouldRender,
+};
+
+/// A route, its switch and its data.
+type View<T, SW, D, E> = (Route<T>, Option<SW>, Rc<LoadState<D, E>>);
+
+/// A borrowed view.
+type ViewRef<'a, T, SW, D, E> = (&'a Route<T>, &'a Option<SW>, &'a Rc<LoadState<D, E>>);
 
 /// Rendering control flow component.
 ///
 /// Based on the current url and its child [Routes](struct.Route.html), it will choose one route and
 /// render its associated component.
 ///
+/// The route is only matched again when it changes, and the Router only re-renders when the
+/// matched switch, its loaded data, or its properties change.
+/// Different routes that produce equal switches don't cause a re-render, which is why the switch
+/// has to implement `Clone` and `PartialEq`.
+/// A new `callback` is used for later messages, but doesn't cause a re-render on its own.
+///
 ///
 /// # Example
 /// ```
@@ -38,7 +60,7 @@ use yew::{virtual_dom::VNode, Callback, Component, ComponentLink, Html, Properti
 /// #   }
 /// }
 ///
-/// #[derive(Switch)]
+/// #[derive(Switch, Clone, PartialEq)]
 /// enum S {
 ///     #[to = "/v"]
 ///     Variant,
@@ -60,18 +82,65 @@ use yew::{virtual_dom::VNode, Callback, Component, ComponentLink, Html, Properti
 ///     }
 /// }
 /// ```
-#[derive(Debug)]
-pub struct Router<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static> {
+pub struct Router<T, SW, M, D = (), E = ()>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    M: 'static,
+    D: 'static,
+    E: 'static,
+{
     route: Route<T>,
-    props: Props<T, SW, M>,
+    /// The switch matched from `route`.
+    switch: Option<SW>,
+    props: Props<T, SW, M, D, E>,
     router_agent: RouteAgentBridge<T>,
+    link: ComponentLink<Self>,
+    load_state: Rc<LoadState<D, E>>,
+    /// The last loaded view, kept while the next route loads.
+    previous: Option<View<T, SW, D, E>>,
+    /// Dropping the task cancels the load.
+    load_task: Option<Box<dyn Task>>,
+    /// Identifies the most recent load, so results of stale loads can be ignored.
+    load_id: u32,
+    /// The view that was rendered before the current transition started.
+    transitions: Transitions<View<T, SW, D, E>>,
+    /// The direction of transitions to the current route.
+    direction: TransitionDirection,
+    /// Dropping the task prevents the current transition from ending.
+    transition_task: Option<TimeoutTask>,
 }
 
-impl<T, SW, M> Router<T, SW, M>
+impl<T, SW, M, D, E> Debug for Router<T, SW, M, D, E>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + Debug + 'static,
+    M: 'static,
+    D: Debug + 'static,
+    E: Debug + 'static,
+{
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        f.debug_struct("Router")
+            .field("route", &self.route)
+            .field("switch", &self.switch)
+            .field("props", &self.props)
+            .field("router_agent", &self.router_agent)
+            .field("load_state", &self.load_state)
+            .field("previous", &self.previous)
+            .field("load_id", &self.load_id)
+            .field("transitions", &self.transitions)
+            .field("direction", &self.direction)
+            .finish()
+    }
+}
+
+impl<T, SW, M, D, E> Router<T, SW, M, D, E>
 where
     T: for<'de> RouterState<'de>,
     SW: Switch + 'static,
     M: 'static,
+    D: 'static,
+    E: 'static,
 {
     /// Wrap a render closure so that it can be used by the Router.
     /// # Example
@@ -79,7 +148,7 @@ where
     /// # use yew_router::Switch;
     /// # use yew_router::router::Router;
     /// # use yew::{html, Html};
-    /// # #[derive(Switch)]
+    /// # #[derive(Switch, Clone, PartialEq)]
     /// # enum S {
     /// #     #[to = "/route"]
     /// #     Variant
@@ -95,81 +164,300 @@ where
     /// });
     /// # }
     /// ```
-    pub fn render<F: RenderFn<Router<T, SW, M>, SW> + 'static>(f: F) -> Render<T, SW, M> {
-        Render::new(f)
+    pub fn render<F: RenderFn<Router<T, SW, M, D, E>, SW> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, D, E> {
+        Render::new(
+            move |context: RouteContext<T, SW, D, E>| f(context.switch),
+            false,
+        )
+    }
+
+    /// Wrap a render closure that also receives the state of the data loaded by the router's
+    /// `loader`.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::{LoadState, Router};
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch, Clone, PartialEq)]
+    /// # enum S {
+    /// #     #[to = "/user/{id}"]
+    /// #     User(usize),
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::render_loaded(
+    ///     |switch: Option<S>, data: &LoadState<String, String>| -> Html<Router<(), S, Msg, String, String>> {
+    ///         match (switch, data) {
+    ///             (Some(S::User(_)), LoadState::Loaded(user)) => html! {{user}},
+    ///             (Some(S::User(_)), LoadState::Failed(error)) => html! {{error}},
+    ///             (Some(S::User(_)), _) => html! {"Loading"},
+    ///             (None, _) => html! {"404"},
+    ///         }
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn render_loaded<F: LoadedRenderFn<Router<T, SW, M, D, E>, SW, D, E> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, D, E> {
+        Render::new(
+            move |context: RouteContext<T, SW, D, E>| f(context.switch, context.load_state),
+            false,
+        )
+    }
+
+    /// Wrap a render closure that receives the whole matched route, including its state and the
+    /// part of it that is left for nested routers.
+    ///
+    /// Unlike the other render functions, it is called again whenever any part of the route
+    /// changes, not only when a different switch is matched.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::{RouteContext, Router};
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch, Clone, PartialEq)]
+    /// # enum S {
+    /// #     #[to = "/docs"]
+    /// #     Docs,
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let render = Router::render_context(
+    ///     |context: RouteContext<(), S>| -> Html<Router<(), S, Msg>> {
+    ///         match context.switch {
+    ///             Some(S::Docs) => html! {
+    ///                 format!("Section {}", context.route.fragment().unwrap_or("top"))
+    ///             },
+    ///             None => html! {"404"},
+    ///         }
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    pub fn render_context<F: ContextRenderFn<Router<T, SW, M, D, E>, T, SW, D, E> + 'static>(
+        f: F,
+    ) -> Render<T, SW, M, D, E> {
+        Render::new(f, true)
+    }
+
+    /// Wrap a closure rendering routes that didn't match, so that it can be used as the Router's
+    /// `not_found` property.
+    /// The closure is given the unmatched route.
+    /// # Example
+    /// ```
+    /// # use yew_router::Switch;
+    /// # use yew_router::router::Router;
+    /// # use yew::{html, Html};
+    /// # #[derive(Switch, Clone, PartialEq)]
+    /// # enum S {
+    /// #     #[to = "/route"]
+    /// #     Variant
+    /// # }
+    /// # pub enum Msg {}
+    ///
+    /// # fn dont_execute() {
+    /// let not_found = Router::render_not_found(|route: &str| -> Html<Router<(), S, Msg>> {
+    ///     html! {format!("{} was not found", route)}
+    /// });
+    /// # }
+    /// ```
+    pub fn render_not_found<F: Fn(&str) -> Html<Router<T, SW, M, D, E>> + 'static>(
+        f: F,
+    ) -> RenderNotFound<T, SW, M, D, E> {
+        RenderNotFound(Rc::new(f), closure_id::<F>())
     }
 }
 
 /// Message for Router.
 #[derive(Debug, Clone)]
-pub enum Msg<T, M> {
+pub enum Msg<T, M, D = (), E = ()> {
     /// Updates the route
-    UpdateRoute(Route<T>),
+    UpdateRoute(RouteChange<T>),
     /// Inner message
     InnerMessage(M),
+    /// The loader identified by the id has finished.
+    DataLoaded(u32, Result<D, E>),
+    /// The transition identified by the id has finished.
+    TransitionEnded(u32),
 }
 
-impl<T, M> From<M> for Msg<T, M> {
+impl<T, M, D, E> From<M> for Msg<T, M, D, E> {
     fn from(inner: M) -> Self {
         Msg::InnerMessage(inner)
     }
 }
 
-// TODO consider removing the Option, and creating two different render functions - one for
-// rendering the switch, and one for a 404 case.
 /// Render function definition
+///
+/// The switch is `None` if no route was matched, unless the Router has a `not_found` render
+/// function, or the switch has a `#[not_found]` variant.
 pub trait RenderFn<CTX: Component, SW>: Fn(Option<SW>) -> Html<CTX> {}
 impl<T, CTX: Component, SW> RenderFn<CTX, SW> for T where T: Fn(Option<SW>) -> Html<CTX> {}
+/// Render function definition for routers with a loader.
+pub trait LoadedRenderFn<CTX: Component, SW, D, E>:
+    Fn(Option<SW>, &LoadState<D, E>) -> Html<CTX>
+{
+}
+impl<T, CTX: Component, SW, D, E> LoadedRenderFn<CTX, SW, D, E> for T where
+    T: Fn(Option<SW>, &LoadState<D, E>) -> Html<CTX>
+{
+}
+/// Render function definition for render functions that are given the whole route.
+pub trait ContextRenderFn<CTX: Component, T, SW, D, E>:
+    for<'a> Fn(RouteContext<'a, T, SW, D, E>) -> Html<CTX>
+{
+}
+impl<F, CTX: Component, T, SW, D, E> ContextRenderFn<CTX, T, SW, D, E> for F where
+    F: for<'a> Fn(RouteContext<'a, T, SW, D, E>) -> Html<CTX>
+{
+}
 /// Owned Render function.
-pub struct Render<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static>(
-    pub(crate) Rc<dyn RenderFn<Router<T, SW, M>, SW>>,
+pub struct Render<
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    M: 'static,
+    D: 'static = (),
+    E: 'static = (),
+>(
+    pub(crate) Rc<dyn ContextRenderFn<Router<T, SW, M, D, E>, T, SW, D, E>>,
+    Option<TypeId>,
+    /// Whether the render function is given the whole route, so that it has to be called again
+    /// when any part of the route changes.
+    bool,
 );
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Render<T, SW, M> {
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> Render<T, SW, M, D, E> {
     /// New render function
-    fn new<F: RenderFn<Router<T, SW, M>, SW> + 'static>(f: F) -> Self {
-        Render(Rc::new(f))
+    fn new<F: ContextRenderFn<Router<T, SW, M, D, E>, T, SW, D, E> + 'static>(
+        f: F,
+        shows_route: bool,
+    ) -> Self {
+        Render(Rc::new(f), closure_id::<F>(), shows_route)
+    }
+}
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> PartialEq for Render<T, SW, M, D, E> {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || (self.1.is_some() && self.1 == other.1)
     }
 }
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Debug for Render<T, SW, M> {
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> Debug for Render<T, SW, M, D, E> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("Render2").finish()
     }
 }
+/// Owned render function for routes that didn't match.
+pub struct RenderNotFound<
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    M: 'static,
+    D: 'static = (),
+    E: 'static = (),
+>(
+    pub(crate) Rc<dyn Fn(&str) -> Html<Router<T, SW, M, D, E>>>,
+    Option<TypeId>,
+);
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> Debug for RenderNotFound<T, SW, M, D, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderNotFound").finish()
+    }
+}
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> PartialEq
+    for RenderNotFound<T, SW, M, D, E>
+{
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0) || (self.1.is_some() && self.1 == other.1)
+    }
+}
 
 /// Properties for Router.
 #[derive(Properties)]
-pub struct Props<T: for<'de> RouterState<'de>, SW: Switch + 'static, M: 'static> {
+pub struct Props<
+    T: for<'de> RouterState<'de>,
+    SW: Switch + 'static,
+    M: 'static,
+    D: 'static = (),
+    E: 'static = (),
+> {
     /// Render fn
     #[props(required)]
-    pub render: Render<T, SW, M>,
+    pub render: Render<T, SW, M, D, E>,
+    /// Renders routes that didn't match instead of calling `render` with `None`.
+    pub not_found: Option<RenderNotFound<T, SW, M, D, E>>,
     /// Optional Callback for propagating messages to parent components.
     pub callback: Option<Callback<M>>,
+    /// Only the part of the route after the scope's prefix is matched.
+    /// Routes outside of the scope are treated as not matching.
+    ///
+    /// This allows a nested router to match the remainder of a route captured by its parent.
+    pub scope: RouteScope,
+    /// Loads data whenever a different switch is matched.
+    /// The state of the load is passed to render functions created with `Router::render_loaded`.
+    ///
+    /// Loads that are still running when the route changes again are cancelled, and their results
+    /// are ignored.
+    pub loader: Option<Loader<SW, D, E>>,
+    /// While the data for a new route is loading, keep rendering the previous route with its data
+    /// instead of rendering the new route in the `Loading` state.
+    pub keep_previous: bool,
+    /// Keeps rendering the previous view next to the new one for a while when the route changes,
+    /// so that the change can be animated.
+    pub transition: Option<Transition>,
 }
 
-impl<T: for<'de> RouterState<'de>, SW: Switch, M> Debug for Props<T, SW, M> {
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> Debug for Props<T, SW, M, D, E> {
     fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
         f.debug_struct("Props").finish()
     }
 }
 
-impl<T, SW, M> Component for Router<T, SW, M>
+impl<T: for<'de> RouterState<'de>, SW: Switch, M, D, E> Props<T, SW, M, D, E> {
+    /// Whether the Router renders the same with these properties as with the other ones.
+    ///
+    /// The callback only receives messages and is recreated whenever a parent renders,
+    /// so it isn't compared.
+    fn renders_like(&self, other: &Self) -> bool {
+        self.render == other.render
+            && self.not_found == other.not_found
+            && self.scope == other.scope
+            && self.loader == other.loader
+            && self.keep_previous == other.keep_previous
+            && self.transition == other.transition
+    }
+}
+
+impl<T, SW, M, D, E> Component for Router<T, SW, M, D, E>
 where
     T: for<'de> RouterState<'de>,
-    SW: Switch + 'static,
+    SW: Switch + Clone + PartialEq + 'static,
     M: 'static,
+    D: 'static,
+    E: 'static,
 {
-    type Message = Msg<T, M>;
-    type Properties = Props<T, SW, M>;
+    type Message = Msg<T, M, D, E>;
+    type Properties = Props<T, SW, M, D, E>;
 
     fn create(props: Self::Properties, mut link: ComponentLink<Self>) -> Self {
         let callback = link.send_back(Msg::UpdateRoute);
-        let router_agent = RouteAgentBridge::new(callback);
+        let router_agent = RouteAgentBridge::with_changes(callback);
 
         Router {
             route: Default::default(), /* This must be updated by immediately requesting a route
                                         * update from the service bridge. */
+            switch: None,
             props,
             router_agent,
+            link,
+            load_state: Rc::new(LoadState::Idle),
+            previous: None,
+            load_task: None,
+            load_id: 0,
+            transitions: Transitions::default(),
+            direction: TransitionDirection::default(),
+            transition_task: None,
         }
     }
 
@@ -180,10 +468,14 @@ where
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::UpdateRoute(route) => {
-                let did_change = self.route != route;
-                self.route = route;
-                did_change
+            Msg::UpdateRoute(change) => {
+                let outgoing = self.outgoing();
+                self.direction = TransitionDirection::from_change(&change);
+                let should_render = self.update_route(change.route);
+                if should_render {
+                    self.transition(outgoing);
+                }
+                should_render
             }
             Msg::InnerMessage(m) => {
                 if let Some(cb) = &self.props.callback {
@@ -191,16 +483,356 @@ where
                 }
                 false
             }
+            Msg::DataLoaded(id, result) => {
+                if id != self.load_id {
+                    return false;
+                }
+                let outgoing = self.outgoing();
+                self.load_task = None;
+                self.previous = None;
+                self.load_state = Rc::new(match result {
+                    Ok(data) => LoadState::Loaded(data),
+                    Err(error) => LoadState::Failed(error),
+                });
+                self.transition(outgoing);
+                true
+            }
+            Msg::TransitionEnded(id) => {
+                if !self.transitions.end(id) {
+                    return false;
+                }
+                self.transition_task = None;
+                true
+            }
         }
     }
 
     fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.props.renders_like(&props) {
+            self.props = props;
+            return false;
+        }
+        let scope_changed = self.props.scope != props.scope;
         self.props = props;
-        true // TODO, this can probably be better now.
+        if scope_changed {
+            let switch = self.switch(self.route.clone());
+            if self.switch != switch {
+                let previous_switch = std::mem::replace(&mut self.switch, switch);
+                self.load(self.route.clone(), previous_switch);
+            }
+        }
+        if self.props.transition.is_none() {
+            self.transition_task = None;
+            self.transitions.cancel();
+        }
+        true
     }
 
     fn view(&self) -> VNode<Self> {
-        let switch: Option<SW> = SW::switch(self.route.clone());
-        (&self.props.render.0)(switch)
+        if self.props.transition.is_none() {
+            let (route, switch, load_state) = self.displayed();
+            return self.render(route, switch, load_state);
+        }
+        let slots = self.transitions.slots();
+        html! {
+            <>
+                {self.render_slot(&slots[0])}
+                {self.render_slot(&slots[1])}
+            </>
+        }
+    }
+}
+
+impl<T, SW, M, D, E> Router<T, SW, M, D, E>
+where
+    T: for<'de> RouterState<'de>,
+    SW: Switch + Clone + PartialEq + 'static,
+    M: 'static,
+    D: 'static,
+    E: 'static,
+{
+    /// Matches the part of the route within the router's scope.
+    fn switch(&self, route: Route<T>) -> Option<SW> {
+        self.props.scope.strip(route).and_then(SW::switch)
+    }
+
+    /// Handles a new route, returning whether the view needs to be rendered again.
+    fn update_route(&mut self, route: Route<T>) -> ShouldRender {
+        let switch = self.switch(route.clone());
+        let redirect = switch
+            .as_ref()
+            .and_then(|switch| switch.redirect())
+            .map(|redirect| self.props.scope.join(&redirect))
+            .filter(|redirect| *redirect != route.route);
+        if let Some(redirect) = redirect {
+            // The agent will respond with the new route once it has been replaced.
+            self.router_agent.send(RouteRequest::ReplaceRoute(Route {
+                route: redirect,
+                state: route.state,
+            }));
+            return false;
+        }
+        if self.route == route {
+            return false;
+        }
+        let update = RouteUpdate::new(
+            (&self.route, &self.switch),
+            (&route, &switch),
+            self.props.not_found.is_some(),
+            self.props.render.2,
+        );
+
+        let previous_route = std::mem::replace(&mut self.route, route);
+        let previous_switch = std::mem::replace(&mut self.switch, switch);
+        let load_changed = update.load && self.load(previous_route, previous_switch);
+        update.render || load_changed
+    }
+
+    /// The view that should be rendered, which is the previous one while the current one is
+    /// loading if `keep_previous` is set.
+    fn displayed(&self) -> ViewRef<T, SW, D, E> {
+        displayed(
+            self.props.keep_previous,
+            (&self.route, &self.switch, &self.load_state),
+            &self.previous,
+        )
+    }
+
+    /// The view that is displayed, if it would have to leave with a transition.
+    fn outgoing(&self) -> Option<View<T, SW, D, E>> {
+        self.props.transition.map(|_| {
+            let (route, switch, load_state) = self.displayed();
+            (route.clone(), switch.clone(), Rc::clone(load_state))
+        })
+    }
+
+    /// Starts a transition away from the outgoing view, if a view for a different route is
+    /// displayed now.
+    fn transition(&mut self, outgoing: Option<View<T, SW, D, E>>) {
+        let (transition, outgoing) = match (self.props.transition, outgoing) {
+            (Some(transition), Some(outgoing)) => (transition, outgoing),
+            _ => return,
+        };
+        if !leaves(&outgoing.0, self.displayed().0) {
+            return;
+        }
+        let id = self.transitions.start(outgoing);
+        let callback = self.link.send_back(move |_: ()| Msg::TransitionEnded(id));
+        self.transition_task = Some(TimeoutService::new().spawn(transition.duration, callback));
+    }
+
+    /// Renders what is in one of the places views are rendered in when transitions are enabled.
+    fn render_slot(&self, slot: &Slot<View<T, SW, D, E>>) -> VNode<Self> {
+        let direction = self.direction.class();
+        match slot {
+            Slot::Displayed => {
+                let (route, switch, load_state) = self.displayed();
+                let class = match self.transitions.leaving() {
+                    Some(_) => format!("route entering {}", direction),
+                    None => "route".to_string(),
+                };
+                html! {
+                    <div class=class>{self.render(route, switch, load_state)}</div>
+                }
+            }
+            Slot::Leaving((route, switch, load_state)) => html! {
+                <div class=format!("route leaving {}", direction)>
+                    {self.render(route, switch, load_state)}
+                </div>
+            },
+            Slot::Empty => html! {},
+        }
+    }
+
+    /// Renders the route using either the `render` or the `not_found` function.
+    fn render(
+        &self,
+        route: &Route<T>,
+        switch: &Option<SW>,
+        load_state: &LoadState<D, E>,
+    ) -> VNode<Self> {
+        match (switch, &self.props.not_found) {
+            (None, Some(not_found)) => (&not_found.0)(&route.route),
+            (switch, _) => (&self.props.render.0)(RouteContext {
+                switch: switch.clone(),
+                route,
+                scope: &self.props.scope,
+                load_state,
+            }),
+        }
+    }
+
+    /// Starts loading the data for the current route, cancelling any load that is still running.
+    ///
+    /// Returns whether the load state changed.
+    fn load(&mut self, previous_route: Route<T>, previous_switch: Option<SW>) -> ShouldRender {
+        self.load_task = None;
+        self.load_id = self.load_id.wrapping_add(1);
+        let loader = match &self.props.loader {
+            Some(loader) => loader.clone(),
+            None => return false,
+        };
+        let switch = match &self.switch {
+            Some(switch) => switch.clone(),
+            None => {
+                self.previous = None;
+                let load_state = std::mem::replace(&mut self.load_state, Rc::new(LoadState::Idle));
+                return match *load_state {
+                    LoadState::Idle => false,
+                    _ => true,
+                };
+            }
+        };
+
+        let load_state = std::mem::replace(&mut self.load_state, Rc::new(LoadState::Loading));
+        if let LoadState::Loaded(_) = *load_state {
+            self.previous = Some((previous_route, previous_switch, load_state));
+        } else if !load_state.is_loading() {
+            // Rapid navigation keeps the route that was last loaded.
+            self.previous = None;
+        }
+
+        let id = self.load_id;
+        let callback = self
+            .link
+            .send_back(move |result: Result<D, E>| Msg::DataLoaded(id, result));
+        // The loader may call the callback right away, in which case the result is handled after
+        // this update has finished.
+        self.load_task = (&loader.0)(&switch, callback);
+        true
+    }
+}
+
+/// What the Router has to do when it moves to a different route.
+#[derive(Debug)]
+struct RouteUpdate {
+    /// Whether the loader runs, which only happens if a different switch was matched.
+    load: bool,
+    /// Whether the view has to be rendered again, regardless of the load.
+    render: bool,
+}
+
+impl RouteUpdate {
+    /// Compares the previous route and its switch to the new ones.
+    ///
+    /// `shows_route` is set if the render function is given the whole route.
+    fn new<T: PartialEq, SW: PartialEq>(
+        (previous_route, previous_switch): (&Route<T>, &Option<SW>),
+        (route, switch): (&Route<T>, &Option<SW>),
+        has_not_found: bool,
+        shows_route: bool,
+    ) -> Self {
+        let switch_changed = previous_switch != switch;
+        let route_changed = if switch.is_none() && has_not_found {
+            // The not found render function is given the route, so it may render differently.
+            previous_route.route != route.route
+        } else {
+            // The fragment, query or state may be rendered as well.
+            shows_route && previous_route != route
+        };
+        RouteUpdate {
+            load: switch_changed,
+            render: switch_changed || route_changed,
+        }
+    }
+}
+
+/// The view that should be rendered, which is the previous one while the current one is loading
+/// if `keep_previous` is set.
+fn displayed<'a, T, SW, D, E>(
+    keep_previous: bool,
+    current: ViewRef<'a, T, SW, D, E>,
+    previous: &'a Option<View<T, SW, D, E>>,
+) -> ViewRef<'a, T, SW, D, E> {
+    match previous {
+        Some((route, switch, load_state)) if keep_previous && current.2.is_loading() => {
+            (route, switch, load_state)
+        }
+        _ => current,
+    }
+}
+
+/// Whether displaying the route after the outgoing one needs a transition.
+///
+/// Moving to a fragment of the same page keeps the view.
+fn leaves<T>(outgoing: &Route<T>, displayed: &Route<T>) -> bool {
+    let ignore_fragment = RouteComparison {
+        ignore_fragment: true,
+        ..RouteComparison::default()
+    };
+    !displayed.eq_with(outgoing, ignore_fragment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestView = View<(), (), (), ()>;
+
+    fn view(route: &str, load_state: LoadState<(), ()>) -> TestView {
+        (Route::from(route), Some(()), Rc::new(load_state))
+    }
+
+    fn route_of(view: ViewRef<(), (), (), ()>) -> &str {
+        &view.0.route
+    }
+
+    #[test]
+    fn displays_previous_view_while_loading() {
+        let (route, switch, load_state) = view("/b", LoadState::Loading);
+        let previous = Some(view("/a", LoadState::Loaded(())));
+        let current = (&route, &switch, &load_state);
+
+        assert_eq!(route_of(displayed(true, current, &previous)), "/a");
+        assert_eq!(route_of(displayed(false, current, &previous)), "/b");
+        assert_eq!(route_of(displayed(true, current, &None)), "/b");
+    }
+
+    #[test]
+    fn displays_current_view_once_loaded() {
+        let (route, switch, load_state) = view("/b", LoadState::Loaded(()));
+        let previous = Some(view("/a", LoadState::Loaded(())));
+
+        let displayed = displayed(true, (&route, &switch, &load_state), &previous);
+        assert_eq!(route_of(displayed), "/b");
+    }
+
+    #[test]
+    fn loads_only_when_switch_changes() {
+        let a = Route::<()>::from("/a");
+        let b = Route::from("/b");
+        let switched = RouteUpdate::new((&a, &Some(1)), (&b, &Some(2)), false, false);
+        assert!(switched.load && switched.render);
+
+        let query = Route::from("/a?q=1");
+        let same = RouteUpdate::new((&a, &Some(1)), (&query, &Some(1)), false, false);
+        assert!(!same.load && !same.render);
+    }
+
+    #[test]
+    fn context_renders_when_only_fragment_changes() {
+        let (top, bottom) = (Route::<()>::from("/a#top"), Route::from("/a#bottom"));
+        let update = RouteUpdate::new((&top, &Some(1)), (&bottom, &Some(1)), false, true);
+        assert!(!update.load && update.render);
+        let update = RouteUpdate::new((&top, &Some(1)), (&bottom, &Some(1)), false, false);
+        assert!(!update.load && !update.render);
+    }
+
+    #[test]
+    fn not_found_renders_for_every_route() {
+        let (a, b) = (Route::<()>::from("/a"), Route::from("/b"));
+        let update = RouteUpdate::new::<(), u8>((&a, &None), (&b, &None), true, false);
+        assert!(!update.load && update.render);
+        let update = RouteUpdate::new::<(), u8>((&a, &None), (&b, &None), false, false);
+        assert!(!update.load && !update.render);
+    }
+
+    #[test]
+    fn transitions_between_routes_but_not_fragments() {
+        let page = Route::<()>::from("/a?q=1#top");
+        assert!(leaves(&page, &Route::from("/b")));
+        assert!(leaves(&page, &Route::from("/a?q=2#top")));
+        assert!(!leaves(&page, &Route::from("/a?q=1#bottom")));
+        assert!(!leaves(&page, &Route::from("/a?q=1")));
     }
 }
@@ -0,0 +1,191 @@
+//! Transitions between the views of consecutive routes.
+use crate::agent::RouteChange;
+use std::time::Duration;
+
+/// Enables transitions between the views rendered by a `Router`.
+///
+/// When a different route is rendered, the view of the new route is rendered in a `div` with the
+/// `route entering` classes, next to the view of the route that was rendered before in a `div`
+/// with the `route leaving` classes.
+/// Both also get the class of the transition's direction, either `forward` or `back`.
+/// Once the duration has passed, the leaving view is removed and the other one loses its
+/// lifecycle classes.
+///
+/// The Router always renders its view in a `div` with the `route` class when transitions are
+/// enabled.
+/// Each view keeps its `div` for as long as it is rendered, so that its components aren't
+/// recreated when a transition starts or ends.
+/// Because of this, the entering view can come before or after the leaving one, and styles
+/// shouldn't depend on their order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transition {
+    /// How long the leaving view is kept.
+    pub duration: Duration,
+}
+
+impl Transition {
+    /// Creates a transition that keeps the leaving view for the given number of milliseconds.
+    pub fn from_millis(millis: u64) -> Self {
+        Transition {
+            duration: Duration::from_millis(millis),
+        }
+    }
+}
+
+/// The direction of a transition, derived from how the new route was reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TransitionDirection {
+    /// The route was pushed or replaced, or the browser moved forward in its history.
+    Forward,
+    /// The browser moved back in its history.
+    Back,
+}
+
+impl TransitionDirection {
+    /// Gets the direction of a transition to the changed route.
+    pub fn from_change<T>(change: &RouteChange<T>) -> Self {
+        if change.went_back() {
+            TransitionDirection::Back
+        } else {
+            TransitionDirection::Forward
+        }
+    }
+
+    /// The class added to both views during the transition.
+    pub fn class(self) -> &'static str {
+        match self {
+            TransitionDirection::Forward => "forward",
+            TransitionDirection::Back => "back",
+        }
+    }
+}
+
+impl Default for TransitionDirection {
+    fn default() -> Self {
+        TransitionDirection::Forward
+    }
+}
+
+/// What is rendered in one of the two places a Router with transitions renders views in.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Slot<'a, V> {
+    /// The view of the current route.
+    Displayed,
+    /// The view that is being transitioned away from.
+    Leaving(&'a V),
+    /// Nothing.
+    Empty,
+}
+
+/// Tracks the view that is leaving and the places both views are rendered in.
+///
+/// A view keeps its place from the moment it is displayed until its transition away from it has
+/// ended, so that its components are never patched into the other view's place.
+/// The entering view takes the place that isn't used by the leaving view.
+#[derive(Debug)]
+pub(crate) struct Transitions<V> {
+    /// The place the displayed view is rendered in.
+    displayed: usize,
+    /// The view that was displayed before the current transition started.
+    leaving: Option<V>,
+    /// Identifies the most recent transition, so only its end removes the leaving view.
+    id: u32,
+}
+
+impl<V> Default for Transitions<V> {
+    fn default() -> Self {
+        Transitions {
+            displayed: 0,
+            leaving: None,
+            id: 0,
+        }
+    }
+}
+
+impl<V> Transitions<V> {
+    /// Starts a transition away from the outgoing view, which keeps its place while the new view
+    /// is displayed in the other one.
+    ///
+    /// Returns the id that ends this transition.
+    pub(crate) fn start(&mut self, outgoing: V) -> u32 {
+        self.id = self.id.wrapping_add(1);
+        self.displayed = 1 - self.displayed;
+        self.leaving = Some(outgoing);
+        self.id
+    }
+
+    /// Ends the transition with the id, returning whether the leaving view was removed.
+    ///
+    /// Transitions that were replaced by a newer one or cancelled don't end.
+    pub(crate) fn end(&mut self, id: u32) -> bool {
+        id == self.id && self.leaving.take().is_some()
+    }
+
+    /// Removes the leaving view right away.
+    pub(crate) fn cancel(&mut self) {
+        self.id = self.id.wrapping_add(1);
+        self.leaving = None;
+    }
+
+    /// The view that is being transitioned away from.
+    pub(crate) fn leaving(&self) -> Option<&V> {
+        self.leaving.as_ref()
+    }
+
+    /// What is rendered in each place.
+    pub(crate) fn slots(&self) -> [Slot<V>; 2] {
+        let other = match &self.leaving {
+            Some(leaving) => Slot::Leaving(leaving),
+            None => Slot::Empty,
+        };
+        if self.displayed == 0 {
+            [Slot::Displayed, other]
+        } else {
+            [other, Slot::Displayed]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaving_view_keeps_its_place() {
+        let mut transitions = Transitions::default();
+        assert_eq!(transitions.slots(), [Slot::Displayed, Slot::Empty]);
+
+        let id = transitions.start("a");
+        assert_eq!(transitions.slots(), [Slot::Leaving(&"a"), Slot::Displayed]);
+
+        assert!(transitions.end(id));
+        assert_eq!(transitions.slots(), [Slot::Empty, Slot::Displayed]);
+
+        transitions.start("b");
+        assert_eq!(transitions.slots(), [Slot::Displayed, Slot::Leaving(&"b")]);
+    }
+
+    #[test]
+    fn newer_transition_replaces_leaving_view() {
+        let mut transitions = Transitions::default();
+        let first = transitions.start("a");
+        let second = transitions.start("b");
+        // The view of "a" is replaced by the entering view, "b" stays where it was displayed.
+        assert_eq!(transitions.slots(), [Slot::Displayed, Slot::Leaving(&"b")]);
+
+        assert!(!transitions.end(first));
+        assert_eq!(transitions.leaving(), Some(&"b"));
+        assert!(transitions.end(second));
+        assert_eq!(transitions.leaving(), None);
+        assert!(!transitions.end(second));
+    }
+
+    #[test]
+    fn cancelled_transition_does_not_end() {
+        let mut transitions = Transitions::default();
+        let id = transitions.start("a");
+        transitions.cancel();
+        assert_eq!(transitions.slots(), [Slot::Empty, Slot::Displayed]);
+        assert!(!transitions.end(id));
+    }
+}
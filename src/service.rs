@@ -1,13 +1,378 @@
 //! Service to handle routing.
 
 use stdweb::{
-    web::{event::PopStateEvent, window, EventListenerHandle, History, IEventTarget, Location},
+    js,
+    unstable::TryFrom,
+    web::{
+        document,
+        event::{ClickEvent, IEvent, IMouseEvent, MouseButton, PopStateEvent},
+        window, EventListenerHandle, History, IEventTarget, Location,
+    },
     Value,
 };
 use yew::callback::Callback;
 
 use crate::route::RouteState;
-use std::marker::PhantomData;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+};
+
+/// The version of the envelope that state is stored in.
+///
+/// This is increased whenever the format of the envelope changes, so that state stored by an
+/// incompatible version isn't misread.
+const STATE_VERSION: u32 = 1;
+
+/// Wraps the state stored in history entries, web storage and messages to other tabs, which is
+/// stored as a JSON string.
+#[derive(Serialize, Deserialize)]
+struct StateEnvelope<T> {
+    version: u32,
+    state: T,
+}
+
+/// The part of the envelope that is read before the state, so that the state isn't decoded if the
+/// envelope has an incompatible version.
+#[derive(Deserialize)]
+struct EnvelopeVersion {
+    version: u32,
+}
+
+/// The reasons state can't be read back.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StateError {
+    /// The state wasn't stored by a `RouteService`, for example because another script on the page
+    /// used the History API directly.
+    Foreign,
+    /// The state was stored in an envelope of a different version.
+    UnsupportedVersion(u32),
+    /// The state couldn't be decoded as the state type, usually because it was stored by a
+    /// different version of the app.
+    Decode(String),
+}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            StateError::Foreign => write!(f, "the state wasn't stored by the router"),
+            StateError::UnsupportedVersion(version) => {
+                write!(f, "the state was stored in envelope version {}", version)
+            }
+            StateError::Decode(error) => write!(f, "the state couldn't be decoded: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// A scroll offset of the page, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScrollPosition {
+    /// Horizontal offset.
+    pub x: f64,
+    /// Vertical offset.
+    pub y: f64,
+}
+
+/// Everything the `RouteService` stores alongside a history entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry<T> {
+    /// The user provided state.
+    pub state: Option<T>,
+    /// The scroll position of the page when it was last left via navigation.
+    pub scroll: Option<ScrollPosition>,
+    /// The position of the entry in the history of the app, starting at `0` for the entry the app
+    /// was first loaded with.
+    ///
+    /// Entries that weren't created by the `RouteAgent` don't have an index.
+    pub index: Option<u32>,
+    /// Identifies the entry when its state is persisted.
+    ///
+    /// Entries are given an id when they are stored by the `RouteService`.
+    pub id: Option<String>,
+}
+
+impl<T> Default for HistoryEntry<T> {
+    fn default() -> Self {
+        HistoryEntry {
+            state: None,
+            scroll: None,
+            index: None,
+            id: None,
+        }
+    }
+}
+
+impl<T> From<T> for HistoryEntry<T> {
+    fn from(state: T) -> Self {
+        HistoryEntry {
+            state: Some(state),
+            scroll: None,
+            index: None,
+            id: None,
+        }
+    }
+}
+
+/// The web storage that history state is persisted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StorageKind {
+    /// `sessionStorage`, which is kept until the tab is closed.
+    Session,
+    /// `localStorage`, which is kept until it is cleared.
+    Local,
+}
+
+impl StorageKind {
+    fn name(self) -> &'static str {
+        match self {
+            StorageKind::Session => "sessionStorage",
+            StorageKind::Local => "localStorage",
+        }
+    }
+}
+
+/// Mirrors the state of history entries into web storage, so it can be restored if the browser
+/// loses it, for example after a crash.
+///
+/// The entry the app is on is remembered in the `sessionStorage` of the tab, so its state can be
+/// found even if the browser loses the id of the entry along with its state, for example when a
+/// crashed tab is restored. Other tabs and fresh visits of the app don't restore it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StatePersistence {
+    /// Where the state is stored.
+    pub storage: StorageKind,
+    /// The maximum number of entries whose state is kept.
+    /// The state of the entries that were stored least recently is removed first, and nothing is
+    /// kept if it is zero.
+    pub capacity: u32,
+    /// Prefixes the keys the state is stored under, which keeps apps on the same origin apart.
+    pub key: String,
+}
+
+impl StatePersistence {
+    /// Persists the state of up to `capacity` entries in `sessionStorage`.
+    pub fn session(capacity: u32) -> Self {
+        StatePersistence {
+            storage: StorageKind::Session,
+            capacity,
+            key: "yew_router".to_string(),
+        }
+    }
+
+    /// Persists the state of up to `capacity` entries in `localStorage`.
+    pub fn local(capacity: u32) -> Self {
+        StatePersistence {
+            storage: StorageKind::Local,
+            capacity,
+            key: "yew_router".to_string(),
+        }
+    }
+
+    /// Stores the state of the entry, evicting the entries stored least recently if there are too
+    /// many.
+    ///
+    /// The entry is also remembered as the current one.
+    fn store(&self, id: &str, route: &str, state: &Value) {
+        let mut ids = self.ids();
+        let evicted = record_id(&mut ids, id, self.capacity as usize);
+        for evicted in &evicted {
+            self.remove_item(self.storage, &self.entry_key(evicted));
+        }
+        if !evicted.iter().any(|evicted| evicted == id) {
+            let entry = PersistedEntry {
+                route: route.to_string(),
+                state: String::try_from(state.clone()).ok(),
+            };
+            if let Ok(json) = serde_json::to_string(&entry) {
+                self.set_item(self.storage, &self.entry_key(id), &json);
+            }
+            self.remember(id);
+        }
+        if let Ok(json) = serde_json::to_string(&ids) {
+            self.set_item(self.storage, &self.key, &json);
+        }
+    }
+
+    /// Remembers the entry with the id as the current one of the tab.
+    ///
+    /// This is kept in `sessionStorage`, so the entry can be found even if the browser loses its
+    /// id along with its state.
+    fn remember(&self, id: &str) {
+        self.set_item(StorageKind::Session, &self.current_key(), id);
+    }
+
+    /// Loads the state of the entry with the id, see `find_entry`.
+    ///
+    /// The entry the tab was on is only considered if the page wasn't freshly navigated to, since
+    /// the browser reopens a page on the entry it was on when it is reloaded or restored.
+    fn load(&self, id: Option<&str>, route: &str) -> Value {
+        let current = if is_fresh_visit() {
+            None
+        } else {
+            self.get_item(StorageKind::Session, &self.current_key())
+        };
+        let current = current.as_ref().map(String::as_str);
+        let read = |id: &str| {
+            self.get_item(self.storage, &self.entry_key(id))
+                .and_then(|json| serde_json::from_str(&json).ok())
+        };
+        match find_entry(read, id, current, route).and_then(|entry| entry.state) {
+            Some(state) => Value::String(state),
+            None => Value::Null,
+        }
+    }
+
+    /// The ids of the stored entries, from least to most recently stored.
+    fn ids(&self) -> Vec<String> {
+        self.get_item(self.storage, &self.key)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn entry_key(&self, id: &str) -> String {
+        format!("{}:{}", self.key, id)
+    }
+
+    fn current_key(&self) -> String {
+        format!("{}:current", self.key)
+    }
+
+    fn get_item(&self, storage: StorageKind, key: &str) -> Option<String> {
+        let value: Value = js! {
+            try {
+                return window[@{storage.name()}].getItem(@{key});
+            } catch (e) {
+                return null;
+            }
+        };
+        String::try_from(value).ok()
+    }
+
+    fn set_item(&self, storage: StorageKind, key: &str, value: &str) {
+        js! { @(no_return)
+            try {
+                window[@{storage.name()}].setItem(@{key}, @{value});
+            } catch (e) {
+                // The storage is full or unavailable.
+            }
+        }
+    }
+
+    fn remove_item(&self, storage: StorageKind, key: &str) {
+        js! { @(no_return)
+            try {
+                window[@{storage.name()}].removeItem(@{key});
+            } catch (e) {
+                // The storage is unavailable.
+            }
+        }
+    }
+}
+
+/// The state of a history entry, as it is persisted in web storage.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct PersistedEntry {
+    route: String,
+    /// The state as written by `state_to_value`.
+    state: Option<String>,
+}
+
+/// Adds the id to the ids of the persisted entries, which are ordered from least to most recently
+/// stored, and removes the ids of the entries that are evicted to stay within the capacity.
+///
+/// Returns the evicted ids, which include the added id itself if the capacity is zero.
+fn record_id(ids: &mut Vec<String>, id: &str, capacity: usize) -> Vec<String> {
+    ids.retain(|other| other != id);
+    ids.push(id.to_string());
+    let excess = ids.len().saturating_sub(capacity);
+    ids.drain(..excess).collect()
+}
+
+/// Finds the persisted entry to restore the state of the current history entry from.
+///
+/// The entry with the id is preferred. If the history entry has no id, or one that wasn't
+/// persisted, it may be the entry the tab was on that lost its id, so `current` is used if it has
+/// the same route. Nothing is restored otherwise, so a fresh visit of a route doesn't get the
+/// state of another entry with the same route.
+fn find_entry<F: Fn(&str) -> Option<PersistedEntry>>(
+    read: F,
+    id: Option<&str>,
+    current: Option<&str>,
+    route: &str,
+) -> Option<PersistedEntry> {
+    if let Some(entry) = id.and_then(&read) {
+        return Some(entry);
+    }
+    current.and_then(read).filter(|entry| entry.route == route)
+}
+
+/// Checks whether the page was navigated to, instead of being reloaded or reopened through the
+/// history.
+fn is_fresh_visit() -> bool {
+    let fresh: Value = js! {
+        try {
+            var entries = performance.getEntriesByType("navigation");
+            if (entries.length > 0) {
+                return entries[0].type === "navigate";
+            }
+            return performance.navigation.type === performance.navigation.TYPE_NAVIGATE;
+        } catch (e) {
+            return false;
+        }
+    };
+    bool::try_from(fresh).unwrap_or(false)
+}
+
+/// Creates an id for a history entry, which is unique with high probability.
+fn new_entry_id() -> String {
+    let id: Value = js! {
+        return Date.now().toString(36) + Math.random().toString(36).slice(2);
+    };
+    String::try_from(id).unwrap_or_default()
+}
+
+/// A message sent between the route services of different tabs of the same origin.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TabMessage<T> {
+    /// The route of another tab has changed.
+    RouteChanged(String, Option<T>),
+    /// Another tab asks to navigate to the route.
+    Navigate(String, Option<T>),
+}
+
+impl<T> TabMessage<T> {
+    /// The kind of the message sent to other tabs.
+    fn kind(&self) -> &'static str {
+        match self {
+            TabMessage::RouteChanged(..) => "route_changed",
+            TabMessage::Navigate(..) => "navigate",
+        }
+    }
+
+    /// The route and state of the message.
+    fn parts(&self) -> (&String, &Option<T>) {
+        match self {
+            TabMessage::RouteChanged(route, state) | TabMessage::Navigate(route, state) => {
+                (route, state)
+            }
+        }
+    }
+
+    /// Creates the message of the kind received from another tab.
+    ///
+    /// Returns `None` for kinds that aren't known.
+    fn from_kind(kind: &str, route: String, state: Option<T>) -> Option<Self> {
+        match kind {
+            "route_changed" => Some(TabMessage::RouteChanged(route, state)),
+            "navigate" => Some(TabMessage::Navigate(route, state)),
+            _ => None,
+        }
+    }
+}
 
 /// A service that facilitates manipulation of the browser's URL bar and responding to browser
 /// 'forward' and 'back' events.
@@ -20,6 +385,12 @@ pub struct RouteService<T> {
     event_listener: Option<EventListenerHandle>, /* maybe this should not be stored in the
                                                   * service itself, and instead returned by
                                                   * register_callback() */
+    click_listener: Option<EventListenerHandle>,
+    /// Stops tracking the scroll position when called.
+    scroll_listener: Option<Value>,
+    /// The channel to other tabs, with `post` and `close` functions.
+    tab_channel: Option<Value>,
+    persistence: Option<StatePersistence>,
     phantom_data: PhantomData<T>,
 }
 
@@ -42,6 +413,10 @@ impl<T> RouteService<T> {
             history: window().history(),
             location,
             event_listener: None,
+            click_listener: None,
+            scroll_listener: None,
+            tab_channel: None,
+            persistence: None,
             phantom_data: PhantomData,
         }
     }
@@ -73,26 +448,502 @@ impl<T> RouteService<T> {
     pub fn get_fragment(&self) -> String {
         self.location.hash().unwrap()
     }
+
+    /// Gets the current scroll position of the page.
+    pub fn get_scroll_position(&self) -> ScrollPosition {
+        let x: Value = js! { return window.pageXOffset; };
+        let y: Value = js! { return window.pageYOffset; };
+        ScrollPosition {
+            x: f64::try_from(x).unwrap_or_default(),
+            y: f64::try_from(y).unwrap_or_default(),
+        }
+    }
+
+    /// Keeps the scroll position stored in the current history entry up to date while the page is
+    /// scrolled, so it is known when the entry is left using the back and forward buttons.
+    ///
+    /// The position is stored shortly after scrolling has stopped, and only in entries that were
+    /// created by this service.
+    pub fn track_scroll_position(&mut self) {
+        self.stop_tracking_scroll_position();
+        let stop = js! {
+            var timeout = null;
+            var listener = function() {
+                // The browser may move to a different entry before the position is stored.
+                var entry = history.state;
+                clearTimeout(timeout);
+                timeout = setTimeout(function() {
+                    if (entry && entry.__yew_router && history.state === entry) {
+                        entry.__yew_router.scroll = { x: window.pageXOffset, y: window.pageYOffset };
+                        history.replaceState(entry, "");
+                    }
+                }, 100);
+            };
+            window.addEventListener("scroll", listener);
+            return function() {
+                clearTimeout(timeout);
+                window.removeEventListener("scroll", listener);
+            };
+        };
+        self.scroll_listener = Some(stop);
+    }
+
+    /// Stops keeping the scroll position stored in the current history entry up to date.
+    pub fn stop_tracking_scroll_position(&mut self) {
+        if let Some(stop) = self.scroll_listener.take() {
+            js! { @(no_return) @{stop}(); }
+        }
+    }
+
+    /// Scrolls the page to the provided position.
+    ///
+    /// Scrolling is deferred until the current task has finished, so that components have a chance
+    /// to render the content of a new route first.
+    pub fn scroll_to(&self, position: ScrollPosition) {
+        js! { @(no_return)
+            var x = @{position.x};
+            var y = @{position.y};
+            setTimeout(function() { window.scrollTo(x, y); }, 0);
+        }
+    }
+
+    /// Scrolls the element whose id matches the fragment into view.
+    /// The fragment may start with a `#`.
+    ///
+    /// Like `scroll_to`, scrolling is deferred until the current task has finished.
+    /// Nothing happens if no matching element exists.
+    pub fn scroll_to_fragment(&self, fragment: &str) {
+        let id = fragment.trim_start_matches('#');
+        if id.is_empty() {
+            return;
+        }
+        js! { @(no_return)
+            var id = @{id};
+            setTimeout(function() {
+                var element = document.getElementById(decodeURIComponent(id));
+                if (element) {
+                    element.scrollIntoView();
+                }
+            }, 0);
+        }
+    }
+
+    /// Intercepts clicks on plain anchor tags anywhere in the document, preventing the page from
+    /// being reloaded and emitting the route of the anchor instead.
+    ///
+    /// Only unmodified primary-button clicks on anchors pointing to the same origin are
+    /// intercepted. Anchors with a `target` other than `_self`, a `download` attribute, or that
+    /// have the `data-router-ignore` attribute set on themselves or an ancestor are left alone.
+    /// Anchors that only point to a fragment of the current page are left alone as well, so the
+    /// browser scrolls to the fragment.
+    pub fn intercept_anchor_clicks(&mut self, callback: Callback<String>) {
+        self.stop_intercepting_anchor_clicks();
+        self.click_listener = Some(document().add_event_listener(move |event: ClickEvent| {
+            if event.default_prevented()
+                || event.button() != MouseButton::Left
+                || event.ctrl_key()
+                || event.meta_key()
+                || event.shift_key()
+                || event.alt_key()
+            {
+                return;
+            }
+            let route: Value = js! {
+                var node = @{event.target()};
+                var anchor = (node && node.closest) ? node.closest("a") : null;
+                if (!anchor || typeof anchor.href !== "string" || !anchor.hasAttribute("href")) {
+                    return null;
+                }
+                var target = anchor.getAttribute("target");
+                if ((target && target !== "_self")
+                    || anchor.hasAttribute("download")
+                    || anchor.closest("[data-router-ignore]")
+                    || anchor.origin !== window.location.origin) {
+                    return null;
+                }
+                if (anchor.hash
+                    && anchor.pathname === window.location.pathname
+                    && anchor.search === window.location.search) {
+                    return null;
+                }
+                return anchor.pathname + anchor.search + anchor.hash;
+            };
+            if let Value::String(route) = route {
+                event.prevent_default();
+                callback.emit(route);
+            }
+        }));
+    }
+
+    /// Stops intercepting clicks on anchor tags.
+    pub fn stop_intercepting_anchor_clicks(&mut self) {
+        if let Some(listener) = self.click_listener.take() {
+            listener.remove();
+        }
+    }
+
+    /// Stops exchanging messages with other tabs.
+    pub fn close_tab_channel(&mut self) {
+        if let Some(channel) = self.tab_channel.take() {
+            js! { @(no_return) @{channel}.close(); }
+        }
+    }
+
+    /// Sets whether the browser should restore scroll positions on its own when navigating with
+    /// the forward and back buttons.
+    pub fn set_browser_scroll_restoration(&self, enabled: bool) {
+        let mode = if enabled { "auto" } else { "manual" };
+        js! { @(no_return)
+            if ("scrollRestoration" in history) {
+                history.scrollRestoration = @{mode};
+            }
+        }
+    }
+
+    /// Moves through the browser's history by the number of entries, backwards if it is negative.
+    ///
+    /// Like pressing the back or forward buttons, this happens asynchronously and triggers a
+    /// `popstate` event once done.
+    pub fn go(&self, delta: i32) {
+        js! { @(no_return) history.go(@{delta}); }
+    }
 }
 
 impl<T> RouteService<T>
 where
     T: RouteState,
 {
+    /// Gets the state stored alongside the current history entry.
+    ///
+    /// Returns `None` if the entry has no state, or if it can't be converted into a `T`.
+    pub fn get_state(&self) -> Option<T> {
+        self.get_entry().state
+    }
+
+    /// Like `get_state`, but fails if the state of the entry can't be converted into a `T`.
+    ///
+    /// If persisted state is restored instead, the state that couldn't be converted is ignored.
+    pub fn try_get_state(&self) -> Result<Option<T>, StateError> {
+        match self.read_entry() {
+            (_, Some(error)) => Err(error),
+            (entry, None) => Ok(entry.state),
+        }
+    }
+
+    /// Gets everything stored alongside the current history entry.
+    ///
+    /// If the entry has no state, or only the default state, and persistence is enabled, the
+    /// persisted state is restored.
+    /// State that can't be decoded is treated as missing.
+    pub fn get_entry(&self) -> HistoryEntry<T> {
+        self.read_entry().0
+    }
+
+    /// Reads the current history entry like `get_entry`, along with the error that made its state
+    /// be dropped, if no state could be read.
+    fn read_entry(&self) -> (HistoryEntry<T>, Option<StateError>) {
+        let value: Value = js! { return history.state; };
+        let (entry, error) = Self::read_entry_value(value);
+        let persistence = match &self.persistence {
+            Some(persistence) if Self::is_unset(&entry.state) => persistence,
+            _ => return (entry, error),
+        };
+        let id = entry.id.as_ref().map(String::as_str);
+        let state = persistence.load(id, &self.get_route());
+        match Self::state_from_value(state) {
+            Ok(Some(state)) => {
+                let entry = HistoryEntry {
+                    state: Some(state),
+                    ..entry
+                };
+                (entry, None)
+            }
+            Ok(None) => (entry, error),
+            Err(persisted) => (entry, error.or(Some(persisted))),
+        }
+    }
+
+    /// Checks if the state is missing, or is the default state which fills in for missing state
+    /// in entries created by the `RouteAgent`.
+    pub fn is_unset(state: &Option<T>) -> bool {
+        match state {
+            Some(state) => {
+                serde_json::to_string(state).ok() == serde_json::to_string(&T::default()).ok()
+            }
+            None => true,
+        }
+    }
+
+    /// Remembers the current history entry as the one the app is on, if persistence is enabled.
+    ///
+    /// This is done whenever an entry is stored, and should be done after the browser moved to a
+    /// different entry.
+    pub fn remember_entry(&self) {
+        let value: Value = js! { return history.state; };
+        if let (Some(persistence), Some(id)) = (&self.persistence, Self::entry_from_value(value).id)
+        {
+            persistence.remember(&id);
+        }
+    }
+
+    /// Checks whether the current history entry can be replaced without losing its state.
+    ///
+    /// This is the case if the entry has no state, or if it was written by this service and its
+    /// state can be decoded. State written by other scripts is left alone.
+    pub fn can_replace_entry(&self) -> bool {
+        let value: Value = js! { return history.state; };
+        Self::state_from_value(Self::state_of_value(&value)).is_ok()
+    }
+
+    /// Enables or disables persisting the state of history entries.
+    ///
+    /// When enabled, the state of the current entry is restored if it is missing, and persisted
+    /// otherwise.
+    pub fn set_persistence(&mut self, persistence: Option<StatePersistence>) {
+        self.persistence = persistence;
+        if self.persistence.is_some() && self.can_replace_entry() {
+            let entry = self.get_entry();
+            let route = self.get_route();
+            self.replace_entry(&route, &entry);
+        }
+    }
+
+    /// Gets the state of an entry written by `entry_to_value`.
+    ///
+    /// Values that weren't written by this service are treated as bare state.
+    fn state_of_value(value: &Value) -> Value {
+        js! {
+            var entry = @{value};
+            return (entry && entry.__yew_router) ? entry.state : entry;
+        }
+    }
+
+    /// Reads an entry written by `entry_to_value`.
+    ///
+    /// State that can't be decoded is treated as missing.
+    fn entry_from_value(value: Value) -> HistoryEntry<T> {
+        Self::read_entry_value(value).0
+    }
+
+    /// Like `entry_from_value`, but also returns the error that made the state be dropped.
+    fn read_entry_value(value: Value) -> (HistoryEntry<T>, Option<StateError>) {
+        let (state, error) = match Self::state_from_value(Self::state_of_value(&value)) {
+            Ok(state) => (state, None),
+            Err(error) => (None, Some(error)),
+        };
+        let scroll: Value = js! {
+            var entry = @{&value};
+            return (entry && entry.__yew_router) ? entry.__yew_router.scroll : null;
+        };
+        let scroll = match scroll {
+            Value::Null | Value::Undefined => None,
+            scroll => {
+                let x: Value = js! { return @{&scroll}.x; };
+                let y: Value = js! { return @{&scroll}.y; };
+                match (f64::try_from(x), f64::try_from(y)) {
+                    (Ok(x), Ok(y)) => Some(ScrollPosition { x, y }),
+                    _ => None,
+                }
+            }
+        };
+        let index: Value = js! {
+            var entry = @{&value};
+            return (entry && entry.__yew_router) ? entry.__yew_router.index : null;
+        };
+        let id: Value = js! {
+            var entry = @{&value};
+            return (entry && entry.__yew_router) ? entry.__yew_router.id : null;
+        };
+        let entry = HistoryEntry {
+            state,
+            scroll,
+            index: u32::try_from(index).ok(),
+            id: String::try_from(id).ok(),
+        };
+        (entry, error)
+    }
+
+    fn entry_to_value(entry: &HistoryEntry<T>) -> Value {
+        let state = Self::state_to_value(&entry.state);
+        let index = entry.index;
+        let id = &entry.id;
+        let scroll = match entry.scroll {
+            Some(ScrollPosition { x, y }) => js! { return { x: @{x}, y: @{y} }; },
+            None => Value::Null,
+        };
+        js! {
+            return {
+                __yew_router: { scroll: @{scroll}, index: @{index}, id: @{id} },
+                state: @{state}
+            };
+        }
+    }
+
+    /// Gives the entry an id if it doesn't have one yet, and persists its state if persistence is
+    /// enabled.
+    fn prepare_entry(&self, route: &str, entry: &HistoryEntry<T>, id: Option<String>) -> Value {
+        let entry = HistoryEntry {
+            id: entry.id.clone().or(id).or_else(|| Some(new_entry_id())),
+            ..entry.clone()
+        };
+        let value = Self::entry_to_value(&entry);
+        if let (Some(persistence), Some(id)) = (&self.persistence, &entry.id) {
+            let state: Value = js! { return @{&value}.state; };
+            persistence.store(id, route, &state);
+        }
+        value
+    }
+
+    /// Encodes the state as JSON, in an envelope of the current version.
+    ///
+    /// State that can't be encoded is logged and treated as missing.
+    fn state_to_value(state: &Option<T>) -> Value {
+        let state = match state {
+            Some(state) => state,
+            None => return Value::Null,
+        };
+        let envelope = StateEnvelope {
+            version: STATE_VERSION,
+            state,
+        };
+        match serde_json::to_string(&envelope) {
+            Ok(json) => Value::String(json),
+            Err(error) => {
+                warn!("The state couldn't be encoded: {}", error);
+                Value::Null
+            }
+        }
+    }
+
+    /// Decodes state written by `state_to_value`.
+    ///
+    /// Returns `Ok(None)` if there is no state.
+    fn state_from_value(value: Value) -> Result<Option<T>, StateError> {
+        let json = match value {
+            Value::Null | Value::Undefined => return Ok(None),
+            Value::String(json) => json,
+            _ => return Err(StateError::Foreign),
+        };
+        let envelope: EnvelopeVersion =
+            serde_json::from_str(&json).map_err(|_| StateError::Foreign)?;
+        if envelope.version != STATE_VERSION {
+            return Err(StateError::UnsupportedVersion(envelope.version));
+        }
+        serde_json::from_str(&json)
+            .map(|envelope: StateEnvelope<T>| Some(envelope.state))
+            .map_err(|error| StateError::Decode(error.to_string()))
+    }
+
+    /// Opens a channel to the tabs of the same origin that opened a channel with the same name,
+    /// closing any channel that was opened before.
+    ///
+    /// A `BroadcastChannel` is used if the browser supports it, otherwise messages are exchanged
+    /// using `storage` events.
+    ///
+    /// State that can't be decoded is treated as missing.
+    pub fn open_tab_channel(&mut self, name: &str, callback: Callback<TabMessage<T>>) {
+        self.close_tab_channel();
+        let receive = move |kind: String, route: String, state: Value| {
+            let state = Self::state_from_value(state).unwrap_or(None);
+            match TabMessage::from_kind(&kind, route, state) {
+                Some(message) => callback.emit(message),
+                None => warn!("Ignoring message of unknown kind {} from another tab", kind),
+            }
+        };
+        let channel = js! {
+            var receive = @{receive};
+            var name = @{name};
+            var handle = function(message) {
+                if (message && message.__yew_router) {
+                    receive(message.kind, message.route, message.state);
+                }
+            };
+            if (typeof BroadcastChannel === "function") {
+                var channel = new BroadcastChannel(name);
+                channel.onmessage = function(event) { handle(event.data); };
+                return {
+                    post: function(message) { channel.postMessage(message); },
+                    close: function() {
+                        channel.close();
+                        receive.drop();
+                    }
+                };
+            }
+            var key = "__yew_router:" + name;
+            var listener = function(event) {
+                if (event.key === key && event.newValue) {
+                    handle(JSON.parse(event.newValue));
+                }
+            };
+            window.addEventListener("storage", listener);
+            return {
+                post: function(message) {
+                    // Storage events are only fired if the value changes.
+                    message.nonce = Math.random();
+                    localStorage.setItem(key, JSON.stringify(message));
+                },
+                close: function() {
+                    window.removeEventListener("storage", listener);
+                    receive.drop();
+                }
+            };
+        };
+        self.tab_channel = Some(channel);
+    }
+
+    /// Sends the message to the other tabs, if a channel has been opened.
+    pub fn post_to_tabs(&self, message: &TabMessage<T>) {
+        let channel = match &self.tab_channel {
+            Some(channel) => channel,
+            None => return,
+        };
+        let (route, state) = message.parts();
+        let state = Self::state_to_value(state);
+        js! { @(no_return)
+            @{channel}.post({
+                __yew_router: true,
+                kind: @{message.kind()},
+                route: @{route},
+                state: @{state}
+            });
+        }
+    }
+
     /// Registers a callback to the route service.
     /// Callbacks will be called when the History API experiences a change such as
     /// popping a state off of its stack when the forward or back buttons are pressed.
-    pub fn register_callback(&mut self, callback: Callback<(String, T)>) {
-        self.event_listener = Some(window().add_event_listener(move |event: PopStateEvent| {
-            let state_value: Value = event.state();
-            let state: T = T::try_from(state_value).unwrap_or_default();
+    ///
+    /// Entries without state are given the default state, while state that can't be decoded is
+    /// passed to the callback as an error.
+    pub fn register_callback(&mut self, callback: Callback<(String, Result<T, StateError>)>) {
+        self.register_pop_listener(move |route, value| {
+            let state = Self::state_from_value(Self::state_of_value(&value));
+            callback.emit((route, state.map(Option::unwrap_or_default)))
+        });
+    }
 
+    /// Registers a callback to the route service that receives the whole history entry.
+    /// Callbacks will be called when the History API experiences a change such as
+    /// popping a state off of its stack when the forward or back buttons are pressed.
+    ///
+    /// Persisted state isn't restored for the entry passed to the callback, `get_entry` can be
+    /// used to read the entry with its state restored instead.
+    /// State that can't be decoded is treated as missing.
+    pub fn register_entry_callback(&mut self, callback: Callback<(String, HistoryEntry<T>)>) {
+        self.register_pop_listener(move |route, value| {
+            callback.emit((route, Self::entry_from_value(value)))
+        });
+    }
+
+    /// Calls the function with the route and the raw history entry whenever an entry is popped.
+    fn register_pop_listener<F: Fn(String, Value) + 'static>(&mut self, f: F) {
+        self.event_listener = Some(window().add_event_listener(move |event: PopStateEvent| {
             // Can't use the existing location, because this is a callback, and can't move it in
             // here.
             let location: Location = window().location().unwrap();
             let route: String = Self::get_route_from_location(&location);
 
-            callback.emit((route.clone(), state))
+            f(route, event.state())
         }));
     }
 
@@ -101,12 +952,187 @@ where
     /// The route should be a relative path that starts with a '/'.
     /// A state object be stored with the url.
     pub fn set_route(&mut self, route: &str, state: T) {
-        self.history.push_state(state, "", Some(route));
+        self.set_entry(route, &HistoryEntry::from(state));
     }
 
     /// Replaces the route with another one removing the most recent history event and
     /// creating another history event in its place.
     pub fn replace_route(&mut self, route: &str, state: T) {
-        let _ = self.history.replace_state(state, "", Some(route));
+        self.replace_entry(route, &HistoryEntry::from(state));
+    }
+
+    /// Like `set_route`, but stores a whole history entry.
+    ///
+    /// Entries without an id are given a new one.
+    pub fn set_entry(&mut self, route: &str, entry: &HistoryEntry<T>) {
+        let value = self.prepare_entry(route, entry, None);
+        self.history.push_state(value, "", Some(route));
+    }
+
+    /// Like `replace_route`, but stores a whole history entry.
+    ///
+    /// Entries without an id keep the id of the entry they replace.
+    pub fn replace_entry(&mut self, route: &str, entry: &HistoryEntry<T>) {
+        let current: Value = js! { return history.state; };
+        let id = Self::entry_from_value(current).id;
+        let value = self.prepare_entry(route, entry, id);
+        let _ = self.history.replace_state(value, "", Some(route));
+    }
+
+    /// Stores the current scroll position in the current history entry, so it can be restored when
+    /// the entry is navigated back to.
+    ///
+    /// Nothing is stored in entries whose state would be lost by replacing them, see
+    /// `can_replace_entry`.
+    pub fn store_scroll_position(&mut self) {
+        if !self.can_replace_entry() {
+            return;
+        }
+        let mut entry = self.get_entry();
+        entry.scroll = Some(self.get_scroll_position());
+        let route = self.get_route();
+        self.replace_entry(&route, &entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    struct State {
+        count: u32,
+    }
+
+    fn decode(json: &str) -> Result<Option<State>, StateError> {
+        RouteService::state_from_value(Value::String(json.to_string()))
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let state = Some(State { count: 3 });
+        let value = RouteService::<State>::state_to_value(&state);
+        assert_eq!(RouteService::<State>::state_from_value(value), Ok(state));
+        let missing = RouteService::<State>::state_from_value(Value::Null);
+        assert_eq!(missing, Ok(None));
+    }
+
+    #[test]
+    fn decode_errors_are_reported() {
+        assert_eq!(decode(r#"{"count":3}"#), Err(StateError::Foreign));
+        assert_eq!(
+            decode(r#"{"version":2,"state":{"count":3}}"#),
+            Err(StateError::UnsupportedVersion(2))
+        );
+        match decode(r#"{"version":1,"state":"3"}"#) {
+            Err(StateError::Decode(_)) => {}
+            other => panic!("expected a decode error, got {:?}", other),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn least_recently_stored_entries_are_evicted() {
+        let mut stored = ids(&["a", "b"]);
+        assert!(record_id(&mut stored, "c", 3).is_empty());
+        assert_eq!(stored, ids(&["a", "b", "c"]));
+
+        // Storing an entry again makes it the most recent one.
+        assert!(record_id(&mut stored, "a", 3).is_empty());
+        assert_eq!(stored, ids(&["b", "c", "a"]));
+
+        assert_eq!(record_id(&mut stored, "d", 2), ids(&["b", "c"]));
+        assert_eq!(stored, ids(&["a", "d"]));
+    }
+
+    #[test]
+    fn nothing_is_stored_without_capacity() {
+        let mut stored = ids(&["a"]);
+        assert_eq!(record_id(&mut stored, "b", 0), ids(&["a", "b"]));
+        assert!(stored.is_empty());
+    }
+
+    #[test]
+    fn persisted_entries_are_found_by_id_then_current_entry() {
+        let entry = |route: &str, state: &str| PersistedEntry {
+            route: route.to_string(),
+            state: Some(state.to_string()),
+        };
+        let mut storage = HashMap::new();
+        storage.insert("a", entry("/x", "1"));
+        storage.insert("b", entry("/y", "2"));
+        storage.insert("c", entry("/x", "3"));
+        let read = |id: &str| storage.get(id).cloned();
+
+        let found = |id, current, route| find_entry(read, id, current, route);
+        assert_eq!(found(Some("a"), Some("c"), "/x"), Some(entry("/x", "1")));
+        // The id of an entry whose state was lost may not have been persisted.
+        assert_eq!(found(Some("new"), Some("a"), "/x"), Some(entry("/x", "1")));
+        assert_eq!(found(None, Some("a"), "/x"), Some(entry("/x", "1")));
+        assert_eq!(found(None, Some("b"), "/x"), None);
+    }
+
+    #[test]
+    fn fresh_visits_of_stored_routes_get_no_state() {
+        let mut storage = HashMap::new();
+        let entry = PersistedEntry {
+            route: "/x".to_string(),
+            state: Some("1".to_string()),
+        };
+        storage.insert("a", entry);
+        let read = |id: &str| storage.get(id).cloned();
+
+        // Fresh visits don't consider the entry the tab was on.
+        assert_eq!(find_entry(read, None, None, "/x"), None);
+        assert_eq!(find_entry(read, Some("new"), None, "/x"), None);
+    }
+
+    #[test]
+    fn persisted_entries_round_trip() {
+        let state = RouteService::<State>::state_to_value(&Some(State { count: 2 }));
+        let entry = PersistedEntry {
+            route: "/a".to_string(),
+            state: String::try_from(state).ok(),
+        };
+        let json = serde_json::to_string(&entry).unwrap();
+        let read: PersistedEntry = serde_json::from_str(&json).unwrap();
+        let state = Value::String(read.state.clone().unwrap());
+        assert_eq!(read, entry);
+        assert_eq!(
+            RouteService::<State>::state_from_value(state),
+            Ok(Some(State { count: 2 }))
+        );
+    }
+
+    #[test]
+    fn default_state_is_unset() {
+        assert!(RouteService::<State>::is_unset(&None));
+        assert!(RouteService::<State>::is_unset(&Some(State::default())));
+        assert!(!RouteService::<State>::is_unset(&Some(State { count: 1 })));
+    }
+
+    #[test]
+    fn tab_messages_round_trip() {
+        let messages = vec![
+            TabMessage::RouteChanged("/a".to_string(), Some(State { count: 1 })),
+            TabMessage::Navigate("/b?c=d".to_string(), None),
+        ];
+        for message in messages {
+            let (route, state) = message.parts();
+            let state = RouteService::<State>::state_to_value(state);
+            let state = RouteService::<State>::state_from_value(state).unwrap();
+            let received = TabMessage::from_kind(message.kind(), route.clone(), state);
+            assert_eq!(received, Some(message));
+        }
+    }
+
+    #[test]
+    fn unknown_tab_messages_are_ignored() {
+        let message = TabMessage::<State>::from_kind("reload", "/".to_string(), None);
+        assert_eq!(message, None);
     }
 }
@@ -41,6 +41,73 @@ use std::fmt::Write;
 ///     Some(TestEnum::CaptureUnnamed("lorem".to_string()))
 /// );
 /// ```
+///
+/// # Fallback
+/// A single variant can be marked with `#[not_found]` (or `#[fallback]`) to be produced when no
+/// other variant matches.
+/// It may hold one field, which is built from the whole unmatched route.
+/// ```
+/// use yew_router::{route::Route, Switch};
+/// #[derive(Debug, Switch, PartialEq)]
+/// enum WithFallback {
+///     #[to = "/home"]
+///     Home,
+///     #[not_found]
+///     NotFound(String),
+/// }
+///
+/// assert_eq!(
+///     WithFallback::switch(Route::<()>::from("/missing")),
+///     Some(WithFallback::NotFound("/missing".to_string()))
+/// );
+/// ```
+///
+/// # Redirect
+/// Variants and structs can be marked with `#[redirect = "/route"]` to make a `Router` replace the
+/// matched route with another one.
+/// Captures in the redirect route are filled in with the fields, by name for named fields and in
+/// order for unnamed fields.
+/// ```
+/// use yew_router::Switch;
+/// #[derive(Debug, Switch, PartialEq, Clone)]
+/// enum Moved {
+///     #[to = "/home"]
+///     #[redirect = "/"]
+///     Home,
+///     #[to = "/old/user/{id}/{tab}"]
+///     #[redirect = "/users/{id}"]
+///     OldUser { id: usize, tab: String },
+///     #[to = "/legacy/{name}/{page}"]
+///     #[redirect = "/articles/{name}/{page}"]
+///     Legacy(String, usize),
+///     #[to = "/current"]
+///     Current,
+/// }
+///
+/// assert_eq!(Moved::Home.redirect(), Some("/".to_string()));
+/// let old_user = Moved::OldUser {
+///     id: 7,
+///     tab: "posts".to_string(),
+/// };
+/// assert_eq!(old_user.redirect(), Some("/users/7".to_string()));
+/// assert_eq!(
+///     Moved::Legacy("intro".to_string(), 2).redirect(),
+///     Some("/articles/intro/2".to_string())
+/// );
+/// assert_eq!(Moved::Current.redirect(), None);
+///
+/// #[derive(Debug, Switch, PartialEq, Clone)]
+/// #[to = "/profile/{name}"]
+/// #[redirect = "/users/{name}"]
+/// struct Profile {
+///     name: String,
+/// }
+///
+/// let profile = Profile {
+///     name: "ferris".to_string(),
+/// };
+/// assert_eq!(profile.redirect(), Some("/users/ferris".to_string()));
+/// ```
 pub trait Switch: Sized {
     /// Based on a route, possibly produce an itself.
     fn switch<T: RouteState>(route: Route<T>) -> Option<Self> {
@@ -64,6 +131,15 @@ pub trait Switch: Sized {
     fn key_not_available() -> Option<Self> {
         None
     }
+
+    /// The route that should replace the current one when this item is matched by a `Router`.
+    ///
+    /// The derive macro implements this for variants annotated with `#[redirect = "/route"]`.
+    /// Captures in the redirect route are filled in with the fields of the variant, so fields of
+    /// variants that redirect must implement `Clone`.
+    fn redirect(&self) -> Option<String> {
+        None
+    }
 }
 
 /// Builds a route from a switch.